@@ -270,6 +270,15 @@ def insert_sample(conn: sqlite3.Connection, ts_ms: int, s: StatusSample, net_rx:
     )
 
 
+def is_collection_paused(conn: sqlite3.Connection) -> bool:
+    """The app can pause recording by setting `collection_paused` in the settings table."""
+    try:
+        row = conn.execute("SELECT value FROM settings WHERE key = 'collection_paused'").fetchone()
+    except sqlite3.Error:
+        return False
+    return bool(row) and row[0] == "true"
+
+
 def prune_old(conn: sqlite3.Connection, keep_days: int = 90) -> None:
     cutoff = now_ms() - keep_days * 86400 * 1000
     conn.execute("DELETE FROM samples WHERE ts_ms < ?", (cutoff,))
@@ -355,7 +364,8 @@ def main() -> int:
             net_rx = None
             net_tx = None
 
-        insert_sample(conn, ts, s, net_rx, net_tx)
+        if not is_collection_paused(conn):
+            insert_sample(conn, ts, s, net_rx, net_tx)
         prune_old(conn, keep_days=args.keep_days)
         conn.commit()
 
@@ -34,5 +34,62 @@ CREATE TABLE IF NOT EXISTS resets (
   at_ts_ms INTEGER NOT NULL
 );
 
+-- Freetext notes attached to a sample timestamp (written by the app)
+CREATE TABLE IF NOT EXISTS annotations (
+  ts_ms INTEGER,
+  session_key TEXT,
+  note TEXT,
+  created_ms INTEGER,
+  PRIMARY KEY (ts_ms, session_key)
+);
+
+-- App-controlled settings (e.g. collection_paused=true|false)
+CREATE TABLE IF NOT EXISTS settings (
+  key TEXT PRIMARY KEY,
+  value TEXT NOT NULL,
+  updated_ms INTEGER NOT NULL
+);
+
+-- Project/grouping tags for sessions (a session may carry several)
+CREATE TABLE IF NOT EXISTS session_tags (
+  session_key TEXT NOT NULL,
+  tag TEXT NOT NULL,
+  PRIMARY KEY (session_key, tag)
+);
+
+-- Alerts fired by the app's check_alerts command
+CREATE TABLE IF NOT EXISTS alert_history (
+  id INTEGER PRIMARY KEY,
+  ts_ms INTEGER,
+  metric TEXT,
+  threshold REAL,
+  value REAL,
+  message TEXT
+);
+
+-- Collector lifecycle events (upgrades, detected restarts), written by the app
+CREATE TABLE IF NOT EXISTS collector_events (
+  ts_ms INTEGER,
+  event_type TEXT,
+  payload TEXT
+);
+
+-- Token budget changes (e.g. a response's budget_tokens), recorded by the app
+CREATE TABLE IF NOT EXISTS budget_adjustment (
+  ts_ms INTEGER,
+  session_key TEXT,
+  old_budget INTEGER,
+  new_budget INTEGER
+);
+
+-- Highest live token rates seen per session, raised by the app's get_live_metrics
+CREATE TABLE IF NOT EXISTS session_peaks (
+  session_key TEXT PRIMARY KEY,
+  peak_tokens_per_s REAL,
+  peak_in_tokens_per_s REAL,
+  peak_out_tokens_per_s REAL,
+  peak_observed_ts_ms INTEGER
+);
+
 CREATE INDEX IF NOT EXISTS idx_samples_ts ON samples(ts_ms);
 CREATE INDEX IF NOT EXISTS idx_samples_session ON samples(session_key);
@@ -0,0 +1,87 @@
+//! Session complexity score for `get_session_complexity_score`.
+//!
+//! Each component is first scaled to [0, 1] against a saturation point, past which it counts as
+//! fully "complex":
+//!
+//! | component                 | scaled value                    |
+//! |---------------------------|---------------------------------|
+//! | context_utilization_pct   | pct / 100                       |
+//! | session_duration_hours    | hours / 4                       |
+//! | model_switches            | switches / 3                    |
+//! | burst_count               | bursts / 10                     |
+//! | mean_tokens_per_request   | tokens / 10_000                 |
+//!
+//! (all clamped to [0, 1]). The score is the weighted mean of the scaled values,
+//! `sum(w_i * x_i) / sum(w_i)`, so it stays in [0, 1] for any non-negative weights. All weights
+//! default to 1; a weight of 0 drops its component.
+
+use serde::{Deserialize, Serialize};
+
+const FULL_DURATION_HOURS: f64 = 4.0;
+const FULL_MODEL_SWITCHES: f64 = 3.0;
+const FULL_BURST_COUNT: f64 = 10.0;
+const FULL_TOKENS_PER_REQUEST: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityComponents {
+    // peak percent_used
+    pub context_utilization_pct: f64,
+    pub session_duration_hours: f64,
+    pub model_switches: i64,
+    // runs of pairs faster than twice the session's mean rate
+    pub burst_count: i64,
+    // mean total_tokens growth per sample that grew
+    pub mean_tokens_per_request: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ComplexityWeights {
+    pub context_utilization: f64,
+    pub duration: f64,
+    pub model_switches: f64,
+    pub bursts: f64,
+    pub tokens_per_request: f64,
+}
+
+impl Default for ComplexityWeights {
+    fn default() -> Self {
+        ComplexityWeights {
+            context_utilization: 1.0,
+            duration: 1.0,
+            model_switches: 1.0,
+            bursts: 1.0,
+            tokens_per_request: 1.0,
+        }
+    }
+}
+
+// Weighted mean of the scaled components; errors on negative weights or when they sum to 0.
+pub fn score(c: &ComplexityComponents, w: &ComplexityWeights) -> Result<f64, String> {
+    let scaled = [
+        (w.context_utilization, c.context_utilization_pct / 100.0),
+        (w.duration, c.session_duration_hours / FULL_DURATION_HOURS),
+        (
+            w.model_switches,
+            c.model_switches as f64 / FULL_MODEL_SWITCHES,
+        ),
+        (w.bursts, c.burst_count as f64 / FULL_BURST_COUNT),
+        (
+            w.tokens_per_request,
+            c.mean_tokens_per_request / FULL_TOKENS_PER_REQUEST,
+        ),
+    ];
+    if scaled.iter().any(|(w, _)| w.is_nan() || *w < 0.0) {
+        return Err("complexity weights must be non-negative".to_string());
+    }
+    let total_weight: f64 = scaled.iter().map(|(w, _)| w).sum();
+    if total_weight <= 0.0 {
+        return Err("complexity weights must not all be 0".to_string());
+    }
+    Ok(scaled
+        .iter()
+        .map(|(w, x)| w * x.clamp(0.0, 1.0))
+        .sum::<f64>()
+        / total_weight)
+}
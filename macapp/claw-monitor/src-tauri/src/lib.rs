@@ -1,6 +1,17 @@
-use rusqlite::Connection;
-use serde::Serialize;
+mod complexity;
+mod log_sql;
+mod model_pricing;
+mod sha256;
+
+use complexity::{ComplexityComponents, ComplexityWeights};
+use log_sql::{timed_query, timed_query_opt};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
 
 fn now_ms() -> i64 {
     SystemTime::now()
@@ -9,6 +20,55 @@ fn now_ms() -> i64 {
         .as_millis() as i64
 }
 
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+// Days since 1970-01-01 -> (year, month, day), proleptic Gregorian (Howard Hinnant's civil_from_days).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+// (year, month, day) -> days since 1970-01-01; inverse of `civil_from_days` (Hinnant's days_from_civil).
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// UTC ms of the most recent local midnight at or before `ts_ms`.
+fn local_day_start_ms(ts_ms: i64, tz_offset_minutes: i32) -> i64 {
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    (ts_ms + offset_ms).div_euclid(DAY_MS) * DAY_MS - offset_ms
+}
+
+// "YYYY-MM-DD HH:00" for a timestamp shifted into the caller's local time.
+fn local_hour_label(ts_ms: i64, tz_offset_minutes: i32) -> String {
+    let local_ms = ts_ms + tz_offset_minutes as i64 * 60 * 1000;
+    let (y, m, d) = civil_from_days(local_ms.div_euclid(DAY_MS));
+    let hour = local_ms.rem_euclid(DAY_MS) / HOUR_MS;
+    format!("{:04}-{:02}-{:02} {:02}:00", y, m, d, hour)
+}
+
+// "YYYY-MM-DD" for a timestamp shifted into the caller's local time.
+fn local_date_label(ts_ms: i64, tz_offset_minutes: i32) -> String {
+    let local_ms = ts_ms + tz_offset_minutes as i64 * 60 * 1000;
+    let (y, m, d) = civil_from_days(local_ms.div_euclid(DAY_MS));
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LiveMetrics {
@@ -31,19 +91,358 @@ pub struct LiveMetrics {
 
     net_rx_bytes_per_s: Option<f64>,
     net_tx_bytes_per_s: Option<f64>,
+
+    // seconds until remaining_tokens runs out at the current output rate
+    context_seconds_remaining: Option<f64>,
+    // percent_used change per minute over the session's last 5 minutes; positive while filling
+    percent_used_per_min: Option<f64>,
+    // minutes until percent_used reaches 100 at that rate
+    estimated_minutes_to_full: Option<f64>,
+    // only filled in when `get_live_metrics` is given a cost config
+    realtime_cost_per_hour_usd: Option<f64>,
+    // session input/output totals at built-in prices (see `model_pricing`)
+    estimated_cost_usd: Option<f64>,
+
+    // how long ago the newest sample was written; a stopped collector shows up as `is_stale`
+    data_age_ms: i64,
+    is_stale: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathSource {
+    Argument,
+    EnvVar,
+    ConfigFile,
+    Default,
+}
+
+fn home_dir() -> String {
+    std::env::var("HOME").unwrap_or_else(|_| "/Users/Shared".to_string())
+}
+
+fn config_file_path() -> String {
+    format!("{}/.openclaw/claw-monitor.toml", home_dir())
+}
+
+// `db_path = "..."` from the config file; only that one key is understood.
+fn config_db_path() -> Option<String> {
+    let text = std::fs::read_to_string(config_file_path()).ok()?;
+    text.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() != "db_path" {
+            return None;
+        }
+        let value = value.trim().trim_matches('"').trim();
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+// `CLAWMONITOR_DB`, unless unset or blank.
+fn env_db_path() -> Option<String> {
+    std::env::var("CLAWMONITOR_DB")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+}
+
+// Environment variable, then config file (as loaded into `AppConfig`), then the default collector
+// location.
+fn resolve_db_path(arg: Option<String>) -> (String, PathSource) {
+    if let Some(p) = arg {
+        return (p, PathSource::Argument);
+    }
+    if let Some(p) = env_db_path() {
+        return (p, PathSource::EnvVar);
+    }
+    if let Some(p) = AppConfig::get().db_path() {
+        return (p, PathSource::ConfigFile);
+    }
+    (
+        format!(
+            "{}/.openclaw/workspace/projects/openclaw-usage-monitor/collector/usage.db",
+            home_dir()
+        ),
+        PathSource::Default,
+    )
 }
 
 fn db_path_default() -> String {
-    if let Ok(p) = std::env::var("CLAWMONITOR_DB") {
-        if !p.trim().is_empty() {
-            return p;
+    resolve_db_path(None).0
+}
+
+// In-memory copy of the config file's settings, read once at startup and kept in sync by
+// `set_db_path_persistent`. Process-wide because every command resolves its default path here.
+pub struct AppConfig {
+    db_path: Mutex<Option<String>>,
+}
+
+impl AppConfig {
+    fn load() -> Self {
+        AppConfig {
+            db_path: Mutex::new(config_db_path()),
+        }
+    }
+
+    fn get() -> &'static AppConfig {
+        static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+        CONFIG.get_or_init(AppConfig::load)
+    }
+
+    fn db_path(&self) -> Option<String> {
+        self.db_path.lock().ok().and_then(|p| p.clone())
+    }
+}
+
+// Replace (or append) the `db_path` line, leaving any other lines in the file alone.
+fn write_config_db_path(path: &str) -> Result<(), String> {
+    let config_path = config_file_path();
+    if let Some(dir) = std::path::Path::new(&config_path).parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let existing = std::fs::read_to_string(&config_path).unwrap_or_default();
+    let entry = format!("db_path = \"{}\"", path);
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in existing.lines() {
+        let is_db_path = line
+            .split_once('=')
+            .is_some_and(|(key, _)| key.trim() == "db_path");
+        if !is_db_path {
+            lines.push(line.to_string());
+        } else if !replaced {
+            lines.push(entry.clone());
+            replaced = true;
+        }
+    }
+    if !replaced {
+        lines.push(entry);
+    }
+    // Write beside the config and rename over it, so a failed write leaves the old file intact.
+    let tmp_path = format!("{}.tmp-{}", config_path, std::process::id());
+    let written = std::fs::write(&tmp_path, lines.join("\n") + "\n")
+        .and_then(|_| std::fs::rename(&tmp_path, &config_path))
+        .map_err(|e| format!("{}: {}", config_path, e));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+// Schema steps in order; `ensure_schema` applies those newer than the recorded `schema_version`.
+// Every table mirrors collector/schema.sql with `IF NOT EXISTS`, so a DB the collector already
+// created only gets the version rows. This is the app's only DDL; commands that write open through
+// `open_db` and can rely on every table here existing.
+const MIGRATIONS: [(i64, &str, &str); 8] = [
+    (
+        1,
+        "create samples",
+        r#"
+        CREATE TABLE IF NOT EXISTS samples (
+            ts_ms INTEGER NOT NULL,
+            session_key TEXT,
+            model TEXT,
+            input_tokens INTEGER,
+            output_tokens INTEGER,
+            total_tokens INTEGER,
+            remaining_tokens INTEGER,
+            context_tokens INTEGER,
+            percent_used INTEGER,
+            net_rx_bytes INTEGER,
+            net_tx_bytes INTEGER,
+            PRIMARY KEY (ts_ms, session_key)
+        );
+        CREATE INDEX IF NOT EXISTS idx_samples_ts ON samples(ts_ms);
+        CREATE INDEX IF NOT EXISTS idx_samples_session ON samples(session_key);
+        "#,
+    ),
+    (
+        2,
+        "create session_peaks",
+        r#"
+        CREATE TABLE IF NOT EXISTS session_peaks (
+            session_key TEXT PRIMARY KEY,
+            peak_tokens_per_s REAL,
+            peak_in_tokens_per_s REAL,
+            peak_out_tokens_per_s REAL,
+            peak_observed_ts_ms INTEGER
+        );
+        "#,
+    ),
+    (
+        3,
+        // the collector reads `collection_paused` from here before every insert
+        "create settings",
+        r#"
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_ms INTEGER NOT NULL
+        );
+        "#,
+    ),
+    (
+        4,
+        "create annotations",
+        r#"
+        CREATE TABLE IF NOT EXISTS annotations (
+            ts_ms INTEGER,
+            session_key TEXT,
+            note TEXT,
+            created_ms INTEGER,
+            PRIMARY KEY (ts_ms, session_key)
+        );
+        "#,
+    ),
+    (
+        5,
+        "create session_tags",
+        r#"
+        CREATE TABLE IF NOT EXISTS session_tags (
+            session_key TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (session_key, tag)
+        );
+        "#,
+    ),
+    (
+        6,
+        "create alert_history",
+        r#"
+        CREATE TABLE IF NOT EXISTS alert_history (
+            id INTEGER PRIMARY KEY,
+            ts_ms INTEGER,
+            metric TEXT,
+            threshold REAL,
+            value REAL,
+            message TEXT
+        );
+        "#,
+    ),
+    (
+        7,
+        "create collector_events",
+        r#"
+        CREATE TABLE IF NOT EXISTS collector_events (
+            ts_ms INTEGER,
+            event_type TEXT,
+            payload TEXT
+        );
+        "#,
+    ),
+    (
+        8,
+        "create budget_adjustment",
+        r#"
+        CREATE TABLE IF NOT EXISTS budget_adjustment (
+            ts_ms INTEGER,
+            session_key TEXT,
+            old_budget INTEGER,
+            new_budget INTEGER
+        );
+        "#,
+    ),
+];
+
+// Idempotent: creates the tables on a fresh file and brings an older one up to date.
+fn ensure_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL);")
+        .map_err(|e| e.to_string())?;
+    let current: i64 = timed_query(
+        conn,
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |r| r.get(0),
+    )?;
+    for (version, name, sql) in MIGRATIONS {
+        if version <= current {
+            continue;
         }
+        conn.execute_batch(&format!(
+            "BEGIN; {} INSERT INTO schema_version (version) VALUES ({}); COMMIT;",
+            sql, version
+        ))
+        .map_err(|e| {
+            let _ = conn.execute_batch("ROLLBACK;");
+            e.to_string()
+        })?;
+        eprintln!("[schema] applied migration {}: {}", version, name);
     }
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/Users/Shared".to_string());
-    format!(
-        "{}/.openclaw/workspace/projects/openclaw-usage-monitor/collector/usage.db",
-        home
+    Ok(())
+}
+
+// Read-write open for the commands that write; also creates or migrates the schema.
+fn open_db(path: &str) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    ensure_schema(&conn)?;
+    Ok(conn)
+}
+
+// Writable connection whose own writes stay in the -wal file, leaving the main file (and so its
+// hash) untouched.
+fn open_no_checkpoint(path: &str) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.set_db_config(
+        rusqlite::config::DbConfig::SQLITE_DBCONFIG_NO_CKPT_ON_CLOSE,
+        true,
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA wal_autocheckpoint = 0;")
+        .map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+// Query-only commands open read-only so they never contend with the collector for the write lock
+// (and never trigger WAL recovery on open). Commands that write use `open_db`.
+fn open_readonly(path: &str) -> Result<Connection, String> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
     )
+    .map_err(|e| e.to_string())
+}
+
+// Read-only connection shared by the polling commands, tagged with the path it was opened for so a
+// different `db_path` argument re-opens it.
+#[derive(Default)]
+pub struct DbState {
+    conn: Mutex<Option<(String, Connection)>>,
+    // set after a failed `session_peaks` write so the poll doesn't retry it every second; cleared
+    // when the path changes
+    peak_writes_failed: AtomicBool,
+}
+
+impl DbState {
+    fn with_conn<T>(
+        &self,
+        path: &str,
+        f: impl FnOnce(&Connection) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut slot = self.conn.lock().map_err(|e| e.to_string())?;
+        if slot.as_ref().is_none_or(|(open_path, _)| open_path != path) {
+            // A read-only handle can't create tables, so bootstrap the schema through a
+            // short-lived writable one first. A DB the app can't write is still readable.
+            if let Err(e) = open_db(path) {
+                eprintln!("[schema] {}: {}", path, e);
+            }
+            *slot = Some((path.to_string(), open_readonly(path)?));
+            self.peak_writes_failed.store(false, Ordering::Relaxed);
+        }
+        let (_, conn) = slot.as_ref().expect("connection opened above");
+        f(conn)
+    }
+
+    // The managed connection is read-only, so the rare peak write gets its own.
+    fn record_session_peak(&self, path: &str, session_key: &str, metrics: &LiveMetrics) {
+        if self.peak_writes_failed.load(Ordering::Relaxed) {
+            return;
+        }
+        let written =
+            open_db(path).and_then(|conn| update_session_peak(&conn, session_key, metrics));
+        if let Err(e) = written {
+            eprintln!("[peaks] {}: {}; not retrying", path, e);
+            self.peak_writes_failed.store(true, Ordering::Relaxed);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -60,58 +459,180 @@ pub struct Rollup {
 
     net_rx_bytes: Option<i64>,
     net_tx_bytes: Option<i64>,
+
+    // window deltas priced by the model of the window's last sample
+    estimated_cost_usd: Option<f64>,
+    // databases with samples in the window; more than 1 only for a multi-DB `get_rollups`
+    source_count: usize,
 }
 
+const WINDOW_DELTA_MAX_DEPTH: u32 = 8;
+
 fn get_window_delta(conn: &Connection, start_ms: i64, end_ms: i64) -> Result<Rollup, String> {
-    // Find first sample >= start and last sample <= end
-    let first = conn
-        .query_row(
+    get_window_delta_recursive(conn, start_ms, end_ms, WINDOW_DELTA_MAX_DEPTH)
+}
+
+// When a session's token counter went backwards inside the window, its tokens are summed over the
+// stretches between resets instead, so usage on either side of a reset still counts. Network
+// counters stay whole-window. `max_depth` bounds the splits per session; at 0 a reset leaves that
+// counter `None`.
+fn get_window_delta_recursive(
+    conn: &Connection,
+    start_ms: i64,
+    end_ms: i64,
+    max_depth: u32,
+) -> Result<Rollup, String> {
+    let whole = window_delta_endpoints(conn, start_ms, end_ms, None)?;
+    let complete = whole.input_tokens.is_some()
+        && whole.output_tokens.is_some()
+        && whole.total_tokens.is_some();
+    if max_depth == 0 || complete {
+        return Ok(whole);
+    }
+    // A None here is just as likely a NULL counter; only recover when a session actually went down.
+    if first_session_reset(conn, start_ms, end_ms, None)?.is_none() {
+        return Ok(whole);
+    }
+
+    let mut stmt = conn
+        .prepare(
             r#"
-            SELECT ts_ms, input_tokens, output_tokens, total_tokens, net_rx_bytes, net_tx_bytes
+            SELECT DISTINCT session_key
             FROM samples
-            WHERE ts_ms >= ?1 AND ts_ms <= ?2
-            ORDER BY ts_ms ASC
-            LIMIT 1
+            WHERE ts_ms >= ?1 AND ts_ms <= ?2 AND session_key IS NOT NULL
             "#,
-            [start_ms, end_ms],
-            |r| {
-                Ok((
-                    r.get::<_, i64>(0)?,
-                    r.get::<_, Option<i64>>(1)?,
-                    r.get::<_, Option<i64>>(2)?,
-                    r.get::<_, Option<i64>>(3)?,
-                    r.get::<_, Option<i64>>(4)?,
-                    r.get::<_, Option<i64>>(5)?,
-                ))
-            },
         )
         .map_err(|e| e.to_string())?;
+    let keys = stmt
+        .query_map([start_ms, end_ms], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
 
-    let last = conn
-        .query_row(
-            r#"
+    let mut tokens = empty_rollup("", start_ms, end_ms);
+    for key in &keys {
+        let r = session_window_delta(conn, key, start_ms, end_ms, max_depth)?;
+        merge_rollup(&mut tokens, &r);
+    }
+    Ok(Rollup {
+        input_tokens: tokens.input_tokens,
+        output_tokens: tokens.output_tokens,
+        total_tokens: tokens.total_tokens,
+        estimated_cost_usd: tokens.estimated_cost_usd,
+        ..whole
+    })
+}
+
+// One session's deltas over the window, split at its first reset and summed, recursively.
+fn session_window_delta(
+    conn: &Connection,
+    session_key: &str,
+    start_ms: i64,
+    end_ms: i64,
+    max_depth: u32,
+) -> Result<Rollup, String> {
+    let whole = window_delta_endpoints(conn, start_ms, end_ms, Some(session_key))?;
+    if max_depth == 0 {
+        return Ok(whole);
+    }
+    let Some(reset_ts) = first_session_reset(conn, start_ms, end_ms, Some(session_key))? else {
+        return Ok(whole);
+    };
+    // (ts_ms, session_key) is the primary key, so the sample before the reset is strictly earlier
+    // and `before` is never empty.
+    let mut before =
+        session_window_delta(conn, session_key, start_ms, reset_ts - 1, max_depth - 1)?;
+    let after = session_window_delta(conn, session_key, reset_ts, end_ms, max_depth - 1)?;
+    merge_rollup(&mut before, &after);
+    before.source_count = 1;
+    Ok(before)
+}
+
+// Timestamp of the first sample whose token counter is below its own session's previous sample.
+fn first_session_reset(
+    conn: &Connection,
+    start_ms: i64,
+    end_ms: i64,
+    session_key: Option<&str>,
+) -> Result<Option<i64>, String> {
+    timed_query_opt(
+        conn,
+        r#"
+            SELECT ts_ms
+            FROM (
+                SELECT ts_ms, input_tokens, output_tokens, total_tokens,
+                       LAG(input_tokens) OVER w AS prev_in,
+                       LAG(output_tokens) OVER w AS prev_out,
+                       LAG(total_tokens) OVER w AS prev_tot
+                FROM samples
+                WHERE ts_ms >= ?1 AND ts_ms <= ?2 AND session_key IS NOT NULL
+                  AND (?3 IS NULL OR session_key = ?3)
+                WINDOW w AS (PARTITION BY session_key ORDER BY ts_ms)
+            )
+            WHERE input_tokens < prev_in OR output_tokens < prev_out OR total_tokens < prev_tot
+            ORDER BY ts_ms ASC
+            LIMIT 1
+            "#,
+        rusqlite::params![start_ms, end_ms, session_key],
+        |r| r.get(0),
+    )
+}
+
+// Plain first-sample to last-sample deltas across the window, over one session or (None) all.
+fn window_delta_endpoints(
+    conn: &Connection,
+    start_ms: i64,
+    end_ms: i64,
+    session_key: Option<&str>,
+) -> Result<Rollup, String> {
+    // Find first sample >= start and last sample <= end
+    let first = timed_query(
+        conn,
+        r#"
             SELECT ts_ms, input_tokens, output_tokens, total_tokens, net_rx_bytes, net_tx_bytes
             FROM samples
-            WHERE ts_ms >= ?1 AND ts_ms <= ?2
+            WHERE ts_ms >= ?1 AND ts_ms <= ?2 AND (?3 IS NULL OR session_key = ?3)
+            ORDER BY ts_ms ASC
+            LIMIT 1
+            "#,
+        rusqlite::params![start_ms, end_ms, session_key],
+        |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, Option<i64>>(1)?,
+                r.get::<_, Option<i64>>(2)?,
+                r.get::<_, Option<i64>>(3)?,
+                r.get::<_, Option<i64>>(4)?,
+                r.get::<_, Option<i64>>(5)?,
+            ))
+        },
+    )?;
+
+    let last = timed_query(
+        conn,
+        r#"
+            SELECT ts_ms, input_tokens, output_tokens, total_tokens, net_rx_bytes, net_tx_bytes, model
+            FROM samples
+            WHERE ts_ms >= ?1 AND ts_ms <= ?2 AND (?3 IS NULL OR session_key = ?3)
             ORDER BY ts_ms DESC
             LIMIT 1
             "#,
-            [start_ms, end_ms],
-            |r| {
-                Ok((
-                    r.get::<_, i64>(0)?,
-                    r.get::<_, Option<i64>>(1)?,
-                    r.get::<_, Option<i64>>(2)?,
-                    r.get::<_, Option<i64>>(3)?,
-                    r.get::<_, Option<i64>>(4)?,
-                    r.get::<_, Option<i64>>(5)?,
-                ))
-            },
-        )
-        .map_err(|e| e.to_string())?;
+        rusqlite::params![start_ms, end_ms, session_key],
+        |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, Option<i64>>(1)?,
+                r.get::<_, Option<i64>>(2)?,
+                r.get::<_, Option<i64>>(3)?,
+                r.get::<_, Option<i64>>(4)?,
+                r.get::<_, Option<i64>>(5)?,
+                r.get::<_, Option<String>>(6)?,
+            ))
+        },
+    )?;
 
     let (ts0, in0, out0, tot0, rx0, tx0) = first;
-    let (ts1, in1, out1, tot1, rx1, tx1) = last;
+    let (ts1, in1, out1, tot1, rx1, tx1, model) = last;
 
     let delta = |a: Option<i64>, b: Option<i64>| match (a, b) {
         (Some(x), Some(y)) => {
@@ -122,64 +643,336 @@ fn get_window_delta(conn: &Connection, start_ms: i64, end_ms: i64) -> Result<Rol
         _ => None,
     };
 
+    let (input_tokens, output_tokens) = (delta(in0, in1), delta(out0, out1));
     Ok(Rollup {
         window_label: "".to_string(),
         start_ts_ms: ts0,
         end_ts_ms: ts1,
-        input_tokens: delta(in0, in1),
-        output_tokens: delta(out0, out1),
+        input_tokens,
+        output_tokens,
         total_tokens: delta(tot0, tot1),
         net_rx_bytes: delta(rx0, rx1),
         net_tx_bytes: delta(tx0, tx1),
+        estimated_cost_usd: model_pricing::estimate_cost_usd(
+            model.as_deref(),
+            input_tokens,
+            output_tokens,
+        ),
+        source_count: 1,
     })
 }
 
-#[tauri::command]
-fn get_rollups(db_path: Option<String>) -> Result<Vec<Rollup>, String> {
-    let db_path = db_path.unwrap_or_else(db_path_default);
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+// Used when get_rollups is called without `windows`.
+const DEFAULT_ROLLUP_WINDOWS: [(&str, i64); 3] = [
+    ("1d", 24 * 60 * 60 * 1000),
+    ("3d", 3 * 24 * 60 * 60 * 1000),
+    ("7d", 7 * 24 * 60 * 60 * 1000),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupsResult {
+    rollups: Vec<Rollup>,
+    // one entry per `db_paths` database that couldn't be opened
+    warnings: Vec<String>,
+}
 
+// With `db_paths`, each database is rolled up over the same windows and the results are summed per
+// window; `db_path` is ignored then.
+#[tauri::command]
+fn get_rollups(
+    db_path: Option<String>,
+    windows: Option<Vec<RollupWindowSpec>>,
+    db_paths: Option<Vec<String>>,
+    db: tauri::State<DbState>,
+) -> Result<RollupsResult, String> {
+    let windows = windows.unwrap_or_else(|| {
+        DEFAULT_ROLLUP_WINDOWS
+            .iter()
+            .map(|(label, duration_ms)| RollupWindowSpec {
+                label: label.to_string(),
+                duration_ms: *duration_ms,
+            })
+            .collect()
+    });
+    for w in &windows {
+        if w.label.trim().is_empty() {
+            return Err("rollup window labels must not be empty".to_string());
+        }
+        if w.duration_ms <= 0 {
+            return Err(format!(
+                "rollup window {:?} must have a positive duration_ms, got {}",
+                w.label, w.duration_ms
+            ));
+        }
+    }
     let end = now_ms();
-    let windows: Vec<(&str, i64)> = vec![
-        ("1d", 24 * 60 * 60 * 1000),
-        ("3d", 3 * 24 * 60 * 60 * 1000),
-        ("7d", 7 * 24 * 60 * 60 * 1000),
-    ];
+    let rollups_for = |conn: &Connection| {
+        windows
+            .iter()
+            .map(|w| labeled_rollup(conn, &w.label, end - w.duration_ms, end))
+            .collect::<Vec<_>>()
+    };
 
-    let mut out = Vec::new();
-    for (label, dur) in windows {
-        let start = end - dur;
-        match get_window_delta(&conn, start, end) {
-            Ok(mut r) => {
-                r.window_label = label.to_string();
-                out.push(r);
-            }
-            Err(_) => {
-                // No samples in this window yet
-                out.push(Rollup {
-                    window_label: label.to_string(),
-                    start_ts_ms: start,
-                    end_ts_ms: end,
-                    input_tokens: None,
-                    output_tokens: None,
-                    total_tokens: None,
-                    net_rx_bytes: None,
-                    net_tx_bytes: None,
-                });
+    let Some(db_paths) = db_paths else {
+        let db_path = db_path.unwrap_or_else(db_path_default);
+        let rollups = db.with_conn(&db_path, |conn| Ok(rollups_for(conn)))?;
+        return Ok(RollupsResult {
+            rollups,
+            warnings: Vec::new(),
+        });
+    };
+
+    let mut rollups: Vec<Rollup> = windows
+        .iter()
+        .map(|w| empty_rollup(&w.label, end - w.duration_ms, end))
+        .collect();
+    let mut warnings = Vec::new();
+    for path in &db_paths {
+        let conn = match open_readonly(path) {
+            Ok(conn) => conn,
+            Err(e) => {
+                warnings.push(format!("{}: {}", path, e));
+                continue;
             }
+        };
+        // Same windows in the same order, so each database's rollups line up by label.
+        for (acc, r) in rollups.iter_mut().zip(rollups_for(&conn)) {
+            merge_rollup(acc, &r);
         }
     }
+    Ok(RollupsResult { rollups, warnings })
+}
 
-    Ok(out)
+fn labeled_rollup(conn: &Connection, label: &str, start: i64, end: i64) -> Rollup {
+    match get_window_delta(conn, start, end) {
+        Ok(mut r) => {
+            r.window_label = label.to_string();
+            r
+        }
+        // No samples in this window yet
+        Err(_) => empty_rollup(label, start, end),
+    }
+}
+
+fn empty_rollup(label: &str, start: i64, end: i64) -> Rollup {
+    Rollup {
+        window_label: label.to_string(),
+        start_ts_ms: start,
+        end_ts_ms: end,
+        input_tokens: None,
+        output_tokens: None,
+        total_tokens: None,
+        net_rx_bytes: None,
+        net_tx_bytes: None,
+        estimated_cost_usd: None,
+        source_count: 0,
+    }
+}
+
+// Adds `r` into `acc`, treating None as "no data" rather than as zero.
+fn merge_rollup(acc: &mut Rollup, r: &Rollup) {
+    if r.source_count == 0 {
+        return;
+    }
+    if acc.source_count == 0 {
+        acc.start_ts_ms = r.start_ts_ms;
+        acc.end_ts_ms = r.end_ts_ms;
+    } else {
+        acc.start_ts_ms = acc.start_ts_ms.min(r.start_ts_ms);
+        acc.end_ts_ms = acc.end_ts_ms.max(r.end_ts_ms);
+    }
+    fn add<T: std::ops::Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+        match (a, b) {
+            (Some(x), Some(y)) => Some(x + y),
+            (x, y) => x.or(y),
+        }
+    }
+    acc.input_tokens = add(acc.input_tokens, r.input_tokens);
+    acc.output_tokens = add(acc.output_tokens, r.output_tokens);
+    acc.total_tokens = add(acc.total_tokens, r.total_tokens);
+    acc.net_rx_bytes = add(acc.net_rx_bytes, r.net_rx_bytes);
+    acc.net_tx_bytes = add(acc.net_tx_bytes, r.net_tx_bytes);
+    acc.estimated_cost_usd = add(acc.estimated_cost_usd, r.estimated_cost_usd);
+    acc.source_count += r.source_count;
+}
+
+const DEFAULT_ROLLUP_CACHE_TTL_MS: i64 = 5000;
+
+// Window deltas keyed by (db_path, start_ms, end_ms); the value carries its expiry timestamp.
+#[derive(Default)]
+pub struct RollupCache {
+    entries: Mutex<HashMap<(String, i64, i64), (Rollup, i64)>>,
+}
+
+impl RollupCache {
+    fn invalidate(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[tauri::command]
+fn get_window_delta_cached(
+    start_ms: i64,
+    end_ms: i64,
+    ttl_ms: Option<i64>,
+    cache: tauri::State<RollupCache>,
+    db_path: Option<String>,
+) -> Result<Rollup, String> {
+    let ttl_ms = ttl_ms.unwrap_or(DEFAULT_ROLLUP_CACHE_TTL_MS);
+    if ttl_ms < 0 {
+        return Err("ttl_ms must not be negative".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let key = (db_path, start_ms, end_ms);
+    let now = now_ms();
+    {
+        let entries = cache.entries.lock().map_err(|e| e.to_string())?;
+        if let Some((rollup, expires_ms)) = entries.get(&key) {
+            if *expires_ms > now {
+                return Ok(rollup.clone());
+            }
+        }
+    }
+
+    // Query without holding the lock so other windows aren't held up behind this one.
+    let conn = open_readonly(&key.0)?;
+    let rollup = get_window_delta(&conn, start_ms, end_ms)?;
+    let mut entries = cache.entries.lock().map_err(|e| e.to_string())?;
+    entries.retain(|_, (_, expires_ms)| *expires_ms > now);
+    entries.insert(key, (rollup.clone(), now.saturating_add(ttl_ms)));
+    Ok(rollup)
 }
 
+// Samples spanned by `get_live_metrics` rates when no `rate_window_n` is given.
+const DEFAULT_RATE_WINDOW_N: usize = 5;
+
+// How far back `LiveMetrics::percent_used_per_min` looks within the session.
+const PERCENT_USED_LOOKBACK_MS: i64 = 5 * 60 * 1000;
+
+// `LiveMetrics::is_stale` threshold when no `stale_threshold_ms` is given.
+const DEFAULT_STALE_THRESHOLD_MS: i64 = 30_000;
+
 #[tauri::command]
-fn get_live_metrics(db_path: Option<String>) -> Result<LiveMetrics, String> {
+fn get_live_metrics(
+    cost_config: Option<CostConfig>,
+    rate_window_n: Option<usize>,
+    stale_threshold_ms: Option<i64>,
+    db_path: Option<String>,
+    db: tauri::State<DbState>,
+) -> Result<LiveMetrics, String> {
     let db_path = db_path.unwrap_or_else(db_path_default);
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    let rate_window_n = rate_window_n.unwrap_or(DEFAULT_RATE_WINDOW_N);
+    let (mut m, new_peak) = db.with_conn(&db_path, |conn| {
+        let m = live_metrics_smoothed(conn, rate_window_n)?;
+        let new_peak = exceeds_session_peak(conn, &m)?;
+        Ok((m, new_peak))
+    })?;
+    if let (true, Some(sk)) = (new_peak, m.session_key.as_deref()) {
+        db.record_session_peak(&db_path, sk, &m);
+    }
+    if let Some(t) = stale_threshold_ms {
+        m.is_stale = m.data_age_ms > t;
+    }
+    if let Some(c) = cost_config {
+        m.realtime_cost_per_hour_usd = realtime_cost_per_hour(&m, &c);
+    }
+    Ok(m)
+}
+
+// Whether any of `m`'s rates beats the stored peak for its session (or none is stored yet, which
+// includes `session_peaks` not existing yet).
+fn exceeds_session_peak(conn: &Connection, m: &LiveMetrics) -> Result<bool, String> {
+    let Some(sk) = &m.session_key else {
+        return Ok(false);
+    };
+    let stored: Option<(Option<f64>, Option<f64>, Option<f64>)> =
+        if table_exists(conn, "session_peaks")? {
+            timed_query_opt(
+                conn,
+                r#"
+                SELECT peak_tokens_per_s, peak_in_tokens_per_s, peak_out_tokens_per_s
+                FROM session_peaks
+                WHERE session_key = ?1
+                "#,
+                [sk],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )?
+        } else {
+            None
+        };
+    let (peak, peak_in, peak_out) = stored.unwrap_or((None, None, None));
+    let beats =
+        |rate: Option<f64>, peak: Option<f64>| rate.is_some_and(|r| peak.is_none_or(|p| r > p));
+    Ok(beats(m.tokens_per_s, peak)
+        || beats(m.in_tokens_per_s, peak_in)
+        || beats(m.out_tokens_per_s, peak_out))
+}
+
+// Raises each stored peak that `metrics` beats; `peak_observed_ts_ms` follows `peak_tokens_per_s`.
+fn update_session_peak(
+    conn: &Connection,
+    session_key: &str,
+    metrics: &LiveMetrics,
+) -> Result<(), String> {
+    conn.execute(
+        r#"
+        INSERT INTO session_peaks (
+            session_key, peak_tokens_per_s, peak_in_tokens_per_s, peak_out_tokens_per_s,
+            peak_observed_ts_ms
+        )
+        VALUES (?1, ?2, ?3, ?4, CASE WHEN ?2 IS NULL THEN NULL ELSE ?5 END)
+        ON CONFLICT(session_key) DO UPDATE SET
+            peak_observed_ts_ms = CASE
+                WHEN excluded.peak_tokens_per_s > COALESCE(peak_tokens_per_s, -1)
+                THEN excluded.peak_observed_ts_ms
+                ELSE peak_observed_ts_ms
+            END,
+            peak_tokens_per_s = MAX(
+                COALESCE(peak_tokens_per_s, excluded.peak_tokens_per_s),
+                COALESCE(excluded.peak_tokens_per_s, peak_tokens_per_s)
+            ),
+            peak_in_tokens_per_s = MAX(
+                COALESCE(peak_in_tokens_per_s, excluded.peak_in_tokens_per_s),
+                COALESCE(excluded.peak_in_tokens_per_s, peak_in_tokens_per_s)
+            ),
+            peak_out_tokens_per_s = MAX(
+                COALESCE(peak_out_tokens_per_s, excluded.peak_out_tokens_per_s),
+                COALESCE(excluded.peak_out_tokens_per_s, peak_out_tokens_per_s)
+            )
+        "#,
+        rusqlite::params![
+            session_key,
+            metrics.tokens_per_s,
+            metrics.in_tokens_per_s,
+            metrics.out_tokens_per_s,
+            metrics.ts_ms
+        ],
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
+}
+
+// USD/hour at the live input/output rates, priced by the live sample's model.
+fn realtime_cost_per_hour(m: &LiveMetrics, cost_config: &CostConfig) -> Option<f64> {
+    let price = cost_config.price_for(m.model.as_deref())?;
+    let (in_rate, out_rate) = (m.in_tokens_per_s?, m.out_tokens_per_s?);
+    let cost_per_s =
+        (in_rate * price.input_usd_per_mtok + out_rate * price.output_usd_per_mtok) / 1_000_000.0;
+    Some(cost_per_s * 3600.0)
+}
+
+fn live_metrics(conn: &Connection) -> Result<LiveMetrics, String> {
+    live_metrics_smoothed(conn, 2)
+}
 
-    // Get most recent sample (any session), then find the previous sample for the SAME session.
-    let (ts1, session_key, model, in1, out1, tot1, rem1, ctx1, pct1, rx1, tx1): (
+// Rates span the last `rate_window_n` samples of the newest sample's session.
+fn live_metrics_smoothed(conn: &Connection, rate_window_n: usize) -> Result<LiveMetrics, String> {
+    // Get most recent sample (any session), then find earlier samples for the SAME session.
+    // Network counters come from `compute_smoothed_rate`'s rows, not this one.
+    let (ts1, session_key, model, in1, out1, tot1, rem1, ctx1, pct1): (
         i64,
         Option<String>,
         Option<String>,
@@ -189,90 +982,72 @@ fn get_live_metrics(db_path: Option<String>) -> Result<LiveMetrics, String> {
         Option<i64>,
         Option<i64>,
         Option<i64>,
-        Option<i64>,
-        Option<i64>,
-    ) = conn
-        .query_row(
-            r#"
+    ) = timed_query(
+        conn,
+        r#"
             SELECT ts_ms, session_key, model,
                    input_tokens, output_tokens, total_tokens, remaining_tokens,
-                   context_tokens, percent_used,
-                   net_rx_bytes, net_tx_bytes
+                   context_tokens, percent_used
             FROM samples
             ORDER BY ts_ms DESC
             LIMIT 1
             "#,
-            [],
-            |r| {
-                Ok((
-                    r.get(0)?,
-                    r.get(1)?,
-                    r.get(2)?,
-                    r.get(3)?,
-                    r.get(4)?,
-                    r.get(5)?,
-                    r.get(6)?,
-                    r.get(7)?,
-                    r.get(8)?,
-                    r.get(9)?,
-                    r.get(10)?,
-                ))
-            },
-        )
-        .map_err(|e| e.to_string())?;
+        [],
+        |r| {
+            Ok((
+                r.get(0)?,
+                r.get(1)?,
+                r.get(2)?,
+                r.get(3)?,
+                r.get(4)?,
+                r.get(5)?,
+                r.get(6)?,
+                r.get(7)?,
+                r.get(8)?,
+            ))
+        },
+    )?;
 
-    let mut tokens_per_s = None;
-    let mut in_tokens_per_s = None;
-    let mut out_tokens_per_s = None;
-    let mut net_rx_bytes_per_s = None;
-    let mut net_tx_bytes_per_s = None;
+    let RateMetrics {
+        tokens_per_s,
+        in_tokens_per_s,
+        out_tokens_per_s,
+        net_rx_bytes_per_s,
+        net_tx_bytes_per_s,
+    } = match &session_key {
+        Some(sk) => compute_smoothed_rate(conn, sk, ts1, rate_window_n),
+        None => RateMetrics::default(),
+    };
+
+    let context_seconds_remaining = match (rem1, out_tokens_per_s) {
+        (Some(rem), Some(rate)) if rate > 0.0 => Some(rem as f64 / rate),
+        _ => None,
+    };
 
-    // If we have a session_key, compute rates against the prior sample for that same session.
-    if let Some(sk) = session_key.clone() {
-        let prev: Result<(i64, Option<i64>, Option<i64>, Option<i64>, Option<i64>, Option<i64>), _> = conn.query_row(
+    let percent_used_per_min = match (&session_key, pct1) {
+        (Some(sk), Some(pct1)) => timed_query_opt(
+            conn,
             r#"
-            SELECT ts_ms, input_tokens, output_tokens, total_tokens, net_rx_bytes, net_tx_bytes
+            SELECT ts_ms, percent_used
             FROM samples
-            WHERE session_key = ?1 AND ts_ms < ?2
-            ORDER BY ts_ms DESC
+            WHERE session_key = ?1 AND ts_ms BETWEEN ?2 AND ?3 AND percent_used IS NOT NULL
+            ORDER BY ts_ms ASC
             LIMIT 1
             "#,
-            rusqlite::params![sk, ts1],
-            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?)),
-        );
+            rusqlite::params![sk, ts1 - PERCENT_USED_LOOKBACK_MS, ts1],
+            |r| Ok((r.get::<_, i64>(0)?, r.get::<_, i64>(1)?)),
+        )?
+        .filter(|(ts0, _)| *ts0 < ts1)
+        .map(|(ts0, pct0)| (pct1 - pct0) as f64 / ((ts1 - ts0) as f64 / 60_000.0)),
+        _ => None,
+    };
+    let estimated_minutes_to_full = match (pct1, percent_used_per_min) {
+        (Some(pct), Some(rate)) if rate > 0.0 => Some((100 - pct).max(0) as f64 / rate),
+        _ => None,
+    };
 
-        if let Ok((ts0, in0, out0, tot0, rx0, tx0)) = prev {
-            let dt_s = (ts1 - ts0) as f64 / 1000.0;
-            if dt_s > 0.0 {
-                if let (Some(a), Some(b)) = (tot1, tot0) {
-                    let d = a - b;
-                    if d >= 0 {
-                        tokens_per_s = Some(d as f64 / dt_s);
-                    }
-                }
-                if let (Some(a), Some(b)) = (in1, in0) {
-                    let d = a - b;
-                    if d >= 0 {
-                        in_tokens_per_s = Some(d as f64 / dt_s);
-                    }
-                }
-                if let (Some(a), Some(b)) = (out1, out0) {
-                    let d = a - b;
-                    if d >= 0 {
-                        out_tokens_per_s = Some(d as f64 / dt_s);
-                    }
-                }
-                if let (Some(a), Some(b)) = (rx1, rx0) {
-                    let d = a - b;
-                    net_rx_bytes_per_s = Some(d as f64 / dt_s);
-                }
-                if let (Some(a), Some(b)) = (tx1, tx0) {
-                    let d = a - b;
-                    net_tx_bytes_per_s = Some(d as f64 / dt_s);
-                }
-            }
-        }
-    }
+    let data_age_ms = now_ms() - ts1;
+    let estimated_cost_usd = model_pricing::estimate_cost_usd(model.as_deref(), in1, out1);
 
     Ok(LiveMetrics {
         ts_ms: ts1,
@@ -289,14 +1064,8412 @@ fn get_live_metrics(db_path: Option<String>) -> Result<LiveMetrics, String> {
         out_tokens_per_s,
         net_rx_bytes_per_s,
         net_tx_bytes_per_s,
+        context_seconds_remaining,
+        percent_used_per_min,
+        estimated_minutes_to_full,
+        realtime_cost_per_hour_usd: None,
+        estimated_cost_usd,
+        data_age_ms,
+        is_stale: data_age_ms > DEFAULT_STALE_THRESHOLD_MS,
     })
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct RateMetrics {
+    tokens_per_s: Option<f64>,
+    in_tokens_per_s: Option<f64>,
+    out_tokens_per_s: Option<f64>,
+    net_rx_bytes_per_s: Option<f64>,
+    net_tx_bytes_per_s: Option<f64>,
+}
+
+// (last - first) / elapsed over the `window_n` most recent samples of `session_key` at or before
+// `latest_ts_ms`; `window_n = 2` is the plain two-sample rate. Token rates are None across a
+// counter reset (see `counter_rate`).
+fn compute_smoothed_rate(
+    conn: &Connection,
+    session_key: &str,
+    latest_ts_ms: i64,
+    window_n: usize,
+) -> RateMetrics {
+    type RateRow = (
+        i64,
+        Option<i64>,
+        Option<i64>,
+        Option<i64>,
+        Option<i64>,
+        Option<i64>,
+    );
+    let rows: Vec<RateRow> = conn
+        .prepare(
+            r#"
+            SELECT ts_ms, input_tokens, output_tokens, total_tokens, net_rx_bytes, net_tx_bytes
+            FROM samples
+            WHERE session_key = ?1 AND ts_ms <= ?2
+            ORDER BY ts_ms DESC
+            LIMIT ?3
+            "#,
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(
+                rusqlite::params![session_key, latest_ts_ms, window_n.max(2) as i64],
+                |r| {
+                    Ok((
+                        r.get(0)?,
+                        r.get(1)?,
+                        r.get(2)?,
+                        r.get(3)?,
+                        r.get(4)?,
+                        r.get(5)?,
+                    ))
+                },
+            )?
+            .collect()
+        })
+        .unwrap_or_default();
+
+    let (Some(last), Some(first)) = (rows.first(), rows.last()) else {
+        return RateMetrics::default();
+    };
+    let (ts1, in1, out1, tot1, rx1, tx1) = *last;
+    let (ts0, in0, out0, tot0, rx0, tx0) = *first;
+    let dt_s = (ts1 - ts0) as f64 / 1000.0;
+    if dt_s <= 0.0 {
+        return RateMetrics::default();
+    }
+    let byte_rate = |first: Option<i64>, last: Option<i64>| Some((last? - first?) as f64 / dt_s);
+    RateMetrics {
+        tokens_per_s: counter_rate(tot0, tot1, dt_s),
+        in_tokens_per_s: counter_rate(in0, in1, dt_s),
+        out_tokens_per_s: counter_rate(out0, out1, dt_s),
+        net_rx_bytes_per_s: byte_rate(rx0, rx1),
+        net_tx_bytes_per_s: byte_rate(tx0, tx1),
+    }
+}
+
+// Column list shared by every query that hydrates a `SampleRow`.
+const SAMPLE_COLUMNS: &str = "ts_ms, session_key, model, \
+    input_tokens, output_tokens, total_tokens, remaining_tokens, \
+    context_tokens, percent_used, net_rx_bytes, net_tx_bytes";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleRow {
+    ts_ms: i64,
+    session_key: Option<String>,
+    model: Option<String>,
+
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    total_tokens: Option<i64>,
+    remaining_tokens: Option<i64>,
+    context_tokens: Option<i64>,
+    percent_used: Option<i64>,
+
+    net_rx_bytes: Option<i64>,
+    net_tx_bytes: Option<i64>,
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool, String> {
+    timed_query(
+        conn,
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |r| r.get::<_, i64>(0),
+    )
+    .map(|n| n > 0)
+    .map_err(|e| e.to_string())
+}
+
+fn sample_from_row(r: &rusqlite::Row) -> rusqlite::Result<SampleRow> {
+    Ok(SampleRow {
+        ts_ms: r.get(0)?,
+        session_key: r.get(1)?,
+        model: r.get(2)?,
+        input_tokens: r.get(3)?,
+        output_tokens: r.get(4)?,
+        total_tokens: r.get(5)?,
+        remaining_tokens: r.get(6)?,
+        context_tokens: r.get(7)?,
+        percent_used: r.get(8)?,
+        net_rx_bytes: r.get(9)?,
+        net_tx_bytes: r.get(10)?,
+    })
+}
+
+fn load_session_samples(conn: &Connection, session_key: &str) -> Result<Vec<SampleRow>, String> {
+    let sql = format!(
+        "SELECT {} FROM samples WHERE session_key = ?1 ORDER BY ts_ms ASC",
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([session_key], sample_from_row)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+// Keyed samples in `[start_ms, end_ms]` (optionally one session), split into per-session runs.
+fn load_samples_by_session_in_range(
+    conn: &Connection,
+    session_key: Option<&str>,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<Vec<Vec<SampleRow>>, String> {
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM samples
+        WHERE session_key IS NOT NULL AND (?1 IS NULL OR session_key = ?1)
+          AND ts_ms >= ?2 AND ts_ms <= ?3
+        ORDER BY session_key ASC, ts_ms ASC
+        "#,
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![session_key, start_ms, end_ms],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(group_by_session(rows))
+}
+
+// Splits time-ordered rows (already sorted by session_key) into per-session runs.
+fn group_by_session(rows: Vec<SampleRow>) -> Vec<Vec<SampleRow>> {
+    let mut sessions: Vec<Vec<SampleRow>> = Vec::new();
+    for row in rows {
+        match sessions.last_mut() {
+            Some(run) if run[0].session_key == row.session_key => run.push(row),
+            _ => sessions.push(vec![row]),
+        }
+    }
+    sessions
+}
+
+// Every keyed sample, split into per-session runs ordered by time.
+fn load_samples_by_session(conn: &Connection) -> Result<Vec<Vec<SampleRow>>, String> {
+    let sql = format!(
+        "SELECT {} FROM samples WHERE session_key IS NOT NULL ORDER BY session_key ASC, ts_ms ASC",
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], sample_from_row)
+        .map_err(|e| e.to_string())?;
+    let rows = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(group_by_session(rows))
+}
+
+// First-to-last change of a monotonic counter; resets make the delta meaningless, same as `get_window_delta`.
+fn counter_delta(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(x), Some(y)) if y >= x => Some(y - x),
+        _ => None,
+    }
+}
+
+// First-to-last deltas for one session.
+#[derive(Debug, Clone)]
+struct SessionTotals {
+    session_key: String,
+    // model of the most recent sample
+    model: Option<String>,
+    first_ts_ms: i64,
+    last_ts_ms: i64,
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    total_tokens: Option<i64>,
+}
+
+fn load_session_totals(conn: &Connection) -> Result<Vec<SessionTotals>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH ranked AS (
+                SELECT session_key, ts_ms, model, input_tokens, output_tokens, total_tokens,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms ASC) AS rn_first,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms DESC) AS rn_last
+                FROM samples
+                WHERE session_key IS NOT NULL
+            )
+            SELECT f.session_key, l.model, f.ts_ms, l.ts_ms,
+                   f.input_tokens, l.input_tokens,
+                   f.output_tokens, l.output_tokens,
+                   f.total_tokens, l.total_tokens
+            FROM ranked f
+            JOIN ranked l ON l.session_key = f.session_key AND l.rn_last = 1
+            WHERE f.rn_first = 1
+            ORDER BY f.ts_ms ASC, f.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(SessionTotals {
+                session_key: r.get(0)?,
+                model: r.get(1)?,
+                first_ts_ms: r.get(2)?,
+                last_ts_ms: r.get(3)?,
+                input_tokens: counter_delta(r.get(4)?, r.get(5)?),
+                output_tokens: counter_delta(r.get(6)?, r.get(7)?),
+                total_tokens: counter_delta(r.get(8)?, r.get(9)?),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    input_usd_per_mtok: f64,
+    output_usd_per_mtok: f64,
+}
+
+// Caller-supplied pricing. A `models` key applies to every model string containing it (so
+// "claude-3-5-sonnet" also prices "claude-3-5-sonnet-20241022"); the longest matching key wins and
+// `default_price` covers everything else.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostConfig {
+    #[serde(default)]
+    models: HashMap<String, ModelPrice>,
+    #[serde(default)]
+    default_price: Option<ModelPrice>,
+}
+
+impl CostConfig {
+    fn price_for(&self, model: Option<&str>) -> Option<&ModelPrice> {
+        model
+            .and_then(|m| {
+                self.models
+                    .iter()
+                    .filter(|(k, _)| m.contains(k.as_str()))
+                    .max_by_key(|(k, _)| k.len())
+                    .map(|(_, p)| p)
+            })
+            .or(self.default_price.as_ref())
+    }
+
+    fn cost_usd(&self, model: Option<&str>, input_tokens: i64, output_tokens: i64) -> Option<f64> {
+        self.price_for(model).map(|p| {
+            (input_tokens as f64 * p.input_usd_per_mtok
+                + output_tokens as f64 * p.output_usd_per_mtok)
+                / 1_000_000.0
+        })
+    }
+}
+
+// Per-second rate of a monotonic counter; `None` across resets or without elapsed time.
+fn counter_rate(a: Option<i64>, b: Option<i64>, dt_s: f64) -> Option<f64> {
+    match (a, b) {
+        (Some(x), Some(y)) if dt_s > 0.0 && y >= x => Some((y - x) as f64 / dt_s),
+        _ => None,
+    }
+}
+
+// tokens/s between two samples of the same session, skipping counter resets like `get_live_metrics`.
+fn pair_rate(a: &SampleRow, b: &SampleRow) -> Option<f64> {
+    let dt_s = (b.ts_ms - a.ts_ms) as f64 / 1000.0;
+    counter_rate(a.total_tokens, b.total_tokens, dt_s)
+}
+
+// (ts_ms of the later sample, tokens/s) for each adjacent pair with a usable rate.
+fn pair_rates(samples: &[SampleRow]) -> Vec<(i64, f64)> {
+    samples
+        .windows(2)
+        .filter_map(|w| pair_rate(&w[0], &w[1]).map(|r| (w[1].ts_ms, r)))
+        .collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+// Population standard deviation.
+fn std_dev(values: &[f64]) -> Option<f64> {
+    let m = mean(values)?;
+    let var = values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len() as f64;
+    Some(var.sqrt())
+}
+
+// Pearson correlation of two equal-length series; `None` if either is constant.
+fn pearson(a: &[f64], b: &[f64]) -> Option<f64> {
+    let (ma, mb) = (mean(a)?, mean(b)?);
+    let (mut cov, mut va, mut vb) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - ma) * (y - mb);
+        va += (x - ma).powi(2);
+        vb += (y - mb).powi(2);
+    }
+    if va == 0.0 || vb == 0.0 {
+        return None;
+    }
+    Some(cov / (va.sqrt() * vb.sqrt()))
+}
+
+// Linear interpolation between closest ranks; `sorted` must be ascending.
+fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64))
+}
+
+// Least-squares fit of y = slope * x + intercept; returns (slope, intercept, r_squared).
+fn linear_regression(points: &[(f64, f64)]) -> Option<(f64, f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let ss_tot: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    let ss_res: f64 = points
+        .iter()
+        .map(|p| (p.1 - (slope * p.0 + intercept)).powi(2))
+        .sum();
+    // A perfectly flat series has nothing to explain; report no fit rather than a perfect one.
+    let r_squared = if ss_tot > 0.0 {
+        1.0 - ss_res / ss_tot
+    } else {
+        0.0
+    };
+    Some((slope, intercept, r_squared))
+}
+
+// Fits that explain less than this share of the variance are reported as flat.
+const TREND_MIN_R_SQUARED: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrendDirection {
+    Rising,
+    Falling,
+    Flat,
+}
+
+fn trend_direction(slope: f64, r_squared: f64) -> TrendDirection {
+    if slope == 0.0 || r_squared < TREND_MIN_R_SQUARED {
+        TrendDirection::Flat
+    } else if slope > 0.0 {
+        TrendDirection::Rising
+    } else {
+        TrendDirection::Falling
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityGridCell {
+    session_key: String,
+    hour_bucket: String,
+    sample_count: i64,
+}
+
+#[tauri::command]
+fn get_session_activity_grid(
+    bucket_hours: u32,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<ActivityGridCell>, String> {
+    if bucket_hours == 0 {
+        return Err("bucket_hours must be at least 1".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Bucket in local time so slots line up with the user's wall clock, then label from the slot start.
+    let bucket_ms = bucket_hours as i64 * HOUR_MS;
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT session_key, (ts_ms + ?1) / ?2 AS bucket, COUNT(*)
+            FROM samples
+            WHERE session_key IS NOT NULL
+            GROUP BY session_key, bucket
+            ORDER BY bucket ASC, session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([offset_ms, bucket_ms], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, i64>(1)?,
+                r.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for row in rows {
+        let (session_key, bucket, sample_count) = row.map_err(|e| e.to_string())?;
+        out.push(ActivityGridCell {
+            session_key,
+            hour_bucket: local_hour_label(bucket * bucket_ms - offset_ms, tz_offset_minutes),
+            sample_count,
+        });
+    }
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSwitchCount {
+    session_key: String,
+    switch_count: i64,
+    // distinct models in order of first appearance
+    models: Vec<String>,
+}
+
+#[tauri::command]
+fn get_model_switch_count(db_path: Option<String>) -> Result<Vec<ModelSwitchCount>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // NULL models (collector carry-forward gaps) never count as a switch: `model != prev_model` is NULL.
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT session_key, SUM(CASE WHEN model != prev_model THEN 1 ELSE 0 END)
+            FROM (
+                SELECT session_key, model,
+                       LAG(model) OVER (PARTITION BY session_key ORDER BY ts_ms) AS prev_model
+                FROM samples
+                WHERE session_key IS NOT NULL
+            )
+            GROUP BY session_key
+            ORDER BY session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let counts = stmt
+        .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::new();
+    for row in counts {
+        let (session_key, switch_count) = row.map_err(|e| e.to_string())?;
+        out.push(ModelSwitchCount {
+            session_key,
+            switch_count,
+            models: Vec::new(),
+        });
+    }
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT session_key, model, MIN(ts_ms) AS first_ts_ms
+            FROM samples
+            WHERE session_key IS NOT NULL AND model IS NOT NULL
+            GROUP BY session_key, model
+            ORDER BY session_key ASC, first_ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let models = stmt
+        .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    for row in models {
+        let (session_key, model) = row.map_err(|e| e.to_string())?;
+        if let Ok(i) = out.binary_search_by(|c| c.session_key.cmp(&session_key)) {
+            out[i].models.push(model);
+        }
+    }
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionThroughput {
+    session_key: String,
+    mean_tokens_per_s: Option<f64>,
+    p95_tokens_per_s: Option<f64>,
+    peak_tokens_per_s: Option<f64>,
+    duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputComparison {
+    session_a_stats: SessionThroughput,
+    session_b_stats: SessionThroughput,
+    a_faster_by_pct: Option<f64>,
+}
+
+fn session_throughput(conn: &Connection, session_key: &str) -> Result<SessionThroughput, String> {
+    let samples = load_session_samples(conn, session_key)?;
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => (f.ts_ms, l.ts_ms),
+        _ => return Err(format!("no samples for session {}", session_key)),
+    };
+
+    let mut rates: Vec<f64> = pair_rates(&samples).into_iter().map(|(_, r)| r).collect();
+    rates.sort_by(|a, b| a.total_cmp(b));
+
+    Ok(SessionThroughput {
+        session_key: session_key.to_string(),
+        mean_tokens_per_s: mean(&rates),
+        p95_tokens_per_s: percentile(&rates, 95.0),
+        peak_tokens_per_s: rates.last().copied(),
+        duration_ms: last - first,
+    })
+}
+
+#[tauri::command]
+fn get_throughput_comparison(
+    session_a: String,
+    session_b: String,
+    db_path: Option<String>,
+) -> Result<ThroughputComparison, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let a = session_throughput(&conn, &session_a)?;
+    let b = session_throughput(&conn, &session_b)?;
+
+    let a_faster_by_pct = match (a.mean_tokens_per_s, b.mean_tokens_per_s) {
+        (Some(ma), Some(mb)) if mb > 0.0 => Some((ma - mb) / mb * 100.0),
+        _ => None,
+    };
+
+    Ok(ThroughputComparison {
+        session_a_stats: a,
+        session_b_stats: b,
+        a_faster_by_pct,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    ts_ms: i64,
+    session_key: String,
+    note: String,
+    created_ms: i64,
+}
+
+#[tauri::command]
+fn annotate_sample(
+    ts_ms: i64,
+    session_key: String,
+    note: String,
+    db_path: Option<String>,
+) -> Result<(), String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+
+    // Re-annotating the same sample replaces the note.
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO annotations (ts_ms, session_key, note, created_ms)
+        VALUES (?1, ?2, ?3, ?4)
+        "#,
+        rusqlite::params![ts_ms, session_key, note, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn load_annotations(conn: &Connection, session_key: &str) -> Result<Vec<Annotation>, String> {
+    if !table_exists(conn, "annotations")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT ts_ms, session_key, note, created_ms
+            FROM annotations
+            WHERE session_key = ?1
+            ORDER BY ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([session_key], |r| {
+            Ok(Annotation {
+                ts_ms: r.get(0)?,
+                session_key: r.get(1)?,
+                note: r.get(2)?,
+                created_ms: r.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_annotations(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<Annotation>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    load_annotations(&conn, &session_key)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatePoint {
+    ts_ms: i64,
+    tokens_per_s: f64,
+    // note attached to this exact sample, when annotations were requested
+    annotation: Option<String>,
+}
+
+#[tauri::command]
+fn get_tokens_per_second_series(
+    session_key: String,
+    include_annotations: Option<bool>,
+    db_path: Option<String>,
+) -> Result<Vec<RatePoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let notes: std::collections::HashMap<i64, String> = if include_annotations.unwrap_or(false) {
+        load_annotations(&conn, &session_key)?
+            .into_iter()
+            .map(|a| (a.ts_ms, a.note))
+            .collect()
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    Ok(pair_rates(&samples)
+        .into_iter()
+        .map(|(ts_ms, tokens_per_s)| RatePoint {
+            ts_ms,
+            tokens_per_s,
+            annotation: notes.get(&ts_ms).cloned(),
+        })
+        .collect())
+}
+
+// Fill rates at or above this many percentage points per minute count as maximum pressure.
+const PRESSURE_FILL_RATE_CEILING_PCT_PER_MIN: f64 = 10.0;
+const PRESSURE_FILL_RATE_LOOKBACK_MS: i64 = 5 * 60 * 1000;
+
+/// "How urgently do I need to summarize?" for one session, in `[0.0, 1.0]`.
+///
+/// `0.5 * used + 0.3 * fill + 0.2 * (1 - remaining / context)` where, from the latest sample:
+/// - `used` is `percent_used / 100`,
+/// - `fill` is the `percent_used` growth per minute since the oldest sample in the last five
+///   minutes, divided by 10 (so 10 points/min or faster saturates at 1.0; shrinking context is 0.0),
+/// - `remaining / context` is `remaining_tokens / context_tokens`.
+///
+/// Each component is clamped to `[0.0, 1.0]`. Returns `None` when any component can't be computed.
+#[tauri::command]
+fn get_context_pressure_index(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let latest = match samples.last() {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    let oldest = samples
+        .iter()
+        .find(|s| s.ts_ms >= latest.ts_ms - PRESSURE_FILL_RATE_LOOKBACK_MS)
+        .unwrap_or(latest);
+
+    let (pct1, remaining, context) = match (
+        latest.percent_used,
+        latest.remaining_tokens,
+        latest.context_tokens,
+    ) {
+        (Some(p), Some(r), Some(c)) if c > 0 => (p, r, c),
+        _ => return Ok(None),
+    };
+    let pct0 = match oldest.percent_used {
+        Some(p) => p,
+        None => return Ok(None),
+    };
+    let dt_min = (latest.ts_ms - oldest.ts_ms) as f64 / 60_000.0;
+    if dt_min <= 0.0 {
+        return Ok(None);
+    }
+
+    let used = (pct1 as f64 / 100.0).clamp(0.0, 1.0);
+    let fill =
+        ((pct1 - pct0) as f64 / dt_min / PRESSURE_FILL_RATE_CEILING_PCT_PER_MIN).clamp(0.0, 1.0);
+    let consumed = (1.0 - remaining as f64 / context as f64).clamp(0.0, 1.0);
+
+    Ok(Some(0.5 * used + 0.3 * fill + 0.2 * consumed))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorHealth {
+    samples_in_window: i64,
+    mean_interval_ms: f64,
+    max_gap_ms: i64,
+    last_sample_age_ms: i64,
+    is_healthy: bool,
+}
+
+#[tauri::command]
+fn get_collector_health(
+    session_key: Option<String>,
+    window_ms: i64,
+    db_path: Option<String>,
+) -> Result<CollectorHealth, String> {
+    if window_ms <= 0 {
+        return Err("window_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    // ?1 IS NULL matches every session, so one statement covers both the global and per-session views.
+    let last_ts: Option<i64> = timed_query(
+        &conn,
+        "SELECT MAX(ts_ms) FROM samples WHERE ?1 IS NULL OR session_key = ?1",
+        [&session_key],
+        |r| r.get(0),
+    )?;
+    let last_ts = last_ts.ok_or_else(|| "no samples recorded yet".to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT ts_ms
+            FROM samples
+            WHERE ts_ms >= ?1 AND ts_ms <= ?2 AND (?3 IS NULL OR session_key = ?3)
+            ORDER BY ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let ts: Vec<i64> = stmt
+        .query_map(rusqlite::params![now - window_ms, now, session_key], |r| {
+            r.get(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let gaps: Vec<i64> = ts.windows(2).map(|w| w[1] - w[0]).collect();
+    let mean_interval_ms = if gaps.is_empty() {
+        0.0
+    } else {
+        gaps.iter().sum::<i64>() as f64 / gaps.len() as f64
+    };
+    let last_sample_age_ms = now - last_ts;
+
+    Ok(CollectorHealth {
+        samples_in_window: ts.len() as i64,
+        mean_interval_ms,
+        max_gap_ms: gaps.iter().copied().max().unwrap_or(0),
+        last_sample_age_ms,
+        // Without at least one interval there's no cadence to compare against.
+        is_healthy: mean_interval_ms > 0.0 && (last_sample_age_ms as f64) < 3.0 * mean_interval_ms,
+    })
+}
+
+// Below this many samples a model's profile is still returned, but flagged as unreliable.
+const MIN_PROFILE_SAMPLES: i64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelProfile {
+    model: String,
+    mean_tokens_per_s: f64,
+    p95_tokens_per_s: f64,
+    mean_context_utilization_pct: f64,
+    session_count: i64,
+    sample_count: i64,
+    low_sample_count: bool,
+}
+
+#[tauri::command]
+fn get_model_performance_profile(db_path: Option<String>) -> Result<Vec<ModelProfile>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    #[derive(Default)]
+    struct Acc {
+        rates: Vec<f64>,
+        pct_sum: f64,
+        pct_n: usize,
+        sessions: usize,
+        samples: i64,
+    }
+
+    let mut by_model: std::collections::BTreeMap<String, Acc> = std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        let mut seen: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        for s in &session {
+            if let Some(m) = s.model.as_deref() {
+                let acc = by_model.entry(m.to_string()).or_default();
+                acc.samples += 1;
+                if let Some(p) = s.percent_used {
+                    acc.pct_sum += p as f64;
+                    acc.pct_n += 1;
+                }
+                if seen.insert(m) {
+                    acc.sessions += 1;
+                }
+            }
+        }
+        // A pair's rate belongs to whichever model produced the later sample.
+        for w in session.windows(2) {
+            if let (Some(m), Some(rate)) = (w[1].model.as_deref(), pair_rate(&w[0], &w[1])) {
+                by_model.entry(m.to_string()).or_default().rates.push(rate);
+            }
+        }
+    }
+
+    let mut out: Vec<ModelProfile> = by_model
+        .into_iter()
+        .map(|(model, mut acc)| {
+            acc.rates.sort_by(|a, b| a.total_cmp(b));
+            ModelProfile {
+                model,
+                mean_tokens_per_s: mean(&acc.rates).unwrap_or(0.0),
+                p95_tokens_per_s: percentile(&acc.rates, 95.0).unwrap_or(0.0),
+                mean_context_utilization_pct: if acc.pct_n > 0 {
+                    acc.pct_sum / acc.pct_n as f64
+                } else {
+                    0.0
+                },
+                session_count: acc.sessions as i64,
+                sample_count: acc.samples,
+                low_sample_count: acc.samples < MIN_PROFILE_SAMPLES,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| b.mean_tokens_per_s.total_cmp(&a.mean_tokens_per_s));
+
+    Ok(out)
+}
+
+const MAX_CONCURRENT_PERIODS: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcurrentPeriod {
+    start_ms: i64,
+    end_ms: i64,
+    session_keys: Vec<String>,
+    overlap_ms: i64,
+}
+
+// (session_key, first_ts_ms, last_ts_ms) for every keyed session, ordered by start.
+fn load_session_spans(conn: &Connection) -> Result<Vec<(String, i64, i64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT session_key, MIN(ts_ms) AS first_ts_ms, MAX(ts_ms)
+            FROM samples
+            WHERE session_key IS NOT NULL
+            GROUP BY session_key
+            ORDER BY first_ts_ms ASC, session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_concurrent_sessions(db_path: Option<String>) -> Result<Vec<ConcurrentPeriod>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let spans = load_session_spans(&conn)?;
+    let mut out = Vec::new();
+    for (i, (key_a, start_a, end_a)) in spans.iter().enumerate() {
+        for (key_b, start_b, end_b) in &spans[i + 1..] {
+            // Spans are sorted by start, so nothing later can overlap `a` once `b` starts after it ends.
+            if start_b > end_a {
+                break;
+            }
+            let start_ms = *start_a.max(start_b);
+            let end_ms = *end_a.min(end_b);
+            if end_ms > start_ms {
+                out.push(ConcurrentPeriod {
+                    start_ms,
+                    end_ms,
+                    session_keys: vec![key_a.clone(), key_b.clone()],
+                    overlap_ms: end_ms - start_ms,
+                });
+            }
+        }
+    }
+    out.sort_by_key(|p| (p.start_ms, p.end_ms));
+    out.truncate(MAX_CONCURRENT_PERIODS);
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetRemaining {
+    quota: i64,
+    used_today: i64,
+    remaining: i64,
+    pct_remaining: f64,
+    projected_exhaustion_ms: Option<i64>,
+}
+
+#[tauri::command]
+fn estimate_remaining_budget(
+    daily_quota_tokens: i64,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<BudgetRemaining, String> {
+    if daily_quota_tokens <= 0 {
+        return Err("daily_quota_tokens must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    remaining_budget(&conn, daily_quota_tokens, tz_offset_minutes)
+}
+
+fn remaining_budget(
+    conn: &Connection,
+    daily_quota_tokens: i64,
+    tz_offset_minutes: i32,
+) -> Result<BudgetRemaining, String> {
+    let now = now_ms();
+    // Same first-to-last delta as the rollups, bounded by the local calendar day.
+    let used_today = get_window_delta(conn, local_day_start_ms(now, tz_offset_minutes), now)
+        .ok()
+        .and_then(|r| r.total_tokens)
+        .unwrap_or(0);
+    let remaining = (daily_quota_tokens - used_today).max(0);
+
+    // Project with the last hour's pace; no usable delta means no projection.
+    let projected_exhaustion_ms = if remaining == 0 {
+        Some(now)
+    } else {
+        get_window_delta(conn, now - HOUR_MS, now)
+            .ok()
+            .and_then(|r| {
+                let span_ms = r.end_ts_ms - r.start_ts_ms;
+                match r.total_tokens {
+                    Some(t) if t > 0 && span_ms > 0 => Some(t as f64 / span_ms as f64),
+                    _ => None,
+                }
+            })
+            .map(|tokens_per_ms| now + (remaining as f64 / tokens_per_ms) as i64)
+    };
+
+    Ok(BudgetRemaining {
+        quota: daily_quota_tokens,
+        used_today,
+        remaining,
+        pct_remaining: remaining as f64 / daily_quota_tokens as f64 * 100.0,
+        projected_exhaustion_ms,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvgRequestSize {
+    avg_total_tokens: f64,
+    avg_input_tokens: f64,
+    avg_output_tokens: f64,
+    total_requests: i64,
+    // The collector doesn't record request boundaries, so this is always an approximation for now.
+    estimated: bool,
+}
+
+#[tauri::command]
+fn get_average_request_size(
+    session_key: Option<String>,
+    start_ms: i64,
+    end_ms: i64,
+    db_path: Option<String>,
+) -> Result<AvgRequestSize, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // No `request_count` column: treat every sample where the session's total_tokens advanced as one
+    // completed request (snapshots typically update once per turn).
+    let mut requests = 0i64;
+    let (mut total, mut input, mut output) = (0i64, 0i64, 0i64);
+    for session in
+        load_samples_by_session_in_range(&conn, session_key.as_deref(), start_ms, end_ms)?
+    {
+        for w in session.windows(2) {
+            let d = |a: Option<i64>, b: Option<i64>| match (a, b) {
+                (Some(x), Some(y)) if y > x => y - x,
+                _ => 0,
+            };
+            let dt = d(w[0].total_tokens, w[1].total_tokens);
+            if dt > 0 {
+                requests += 1;
+                total += dt;
+                input += d(w[0].input_tokens, w[1].input_tokens);
+                output += d(w[0].output_tokens, w[1].output_tokens);
+            }
+        }
+    }
+
+    let avg = |n: i64| {
+        if requests > 0 {
+            n as f64 / requests as f64
+        } else {
+            0.0
+        }
+    };
+    Ok(AvgRequestSize {
+        avg_total_tokens: avg(total),
+        avg_input_tokens: avg(input),
+        avg_output_tokens: avg(output),
+        total_requests: requests,
+        estimated: true,
+    })
+}
+
+const MAX_REPLAY_FRAMES: i64 = 20_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayFrame {
+    ts_ms: i64,
+    tokens_per_s: Option<f64>,
+    percent_used: Option<i64>,
+    net_rx_bytes_per_s: Option<f64>,
+}
+
+#[tauri::command]
+fn get_session_replay(
+    session_key: String,
+    resolution_ms: i64,
+    db_path: Option<String>,
+) -> Result<Vec<ReplayFrame>, String> {
+    let resolution_ms = resolution_ms.clamp(100, 60_000);
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => (f.ts_ms, l.ts_ms),
+        _ => return Ok(Vec::new()),
+    };
+    if (last - first) / resolution_ms + 1 > MAX_REPLAY_FRAMES {
+        return Err(format!(
+            "session spans more than {} frames at {} ms; use a coarser resolution",
+            MAX_REPLAY_FRAMES, resolution_ms
+        ));
+    }
+
+    let mut out = Vec::new();
+    let mut i = 0;
+    let mut tick = first;
+    while tick <= last {
+        // Ticks only move forward, so advance to the last sample at or before the tick and compare it with the next one.
+        while i + 1 < samples.len() && samples[i + 1].ts_ms <= tick {
+            i += 1;
+        }
+        let nearest = [Some(i), (i + 1 < samples.len()).then_some(i + 1)]
+            .into_iter()
+            .flatten()
+            .min_by_key(|&j| (samples[j].ts_ms - tick).abs())
+            .filter(|&j| (samples[j].ts_ms - tick).abs() <= 2 * resolution_ms);
+
+        let frame = match nearest {
+            Some(j) => {
+                let prev = j.checked_sub(1).map(|k| &samples[k]);
+                let s = &samples[j];
+                ReplayFrame {
+                    ts_ms: tick,
+                    tokens_per_s: prev.and_then(|p| pair_rate(p, s)),
+                    percent_used: s.percent_used,
+                    net_rx_bytes_per_s: prev.and_then(|p| {
+                        let dt_s = (s.ts_ms - p.ts_ms) as f64 / 1000.0;
+                        counter_rate(p.net_rx_bytes, s.net_rx_bytes, dt_s)
+                    }),
+                }
+            }
+            None => ReplayFrame {
+                ts_ms: tick,
+                tokens_per_s: None,
+                percent_used: None,
+                net_rx_bytes_per_s: None,
+            },
+        };
+        out.push(frame);
+        tick += resolution_ms;
+    }
+
+    Ok(out)
+}
+
+fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    if !table_exists(conn, "settings")? {
+        return Ok(None);
+    }
+    timed_query(
+        conn,
+        "SELECT (SELECT value FROM settings WHERE key = ?1)",
+        [key],
+        |r| r.get(0),
+    )
+}
+
+fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value, updated_ms) VALUES (?1, ?2, ?3)",
+        rusqlite::params![key, value, now_ms()],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn collection_paused(conn: &Connection) -> Result<bool, String> {
+    Ok(get_setting(conn, "collection_paused")?.as_deref() == Some("true"))
+}
+
+#[tauri::command]
+fn set_collection_paused(paused: bool, db_path: Option<String>) -> Result<(), String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+    set_setting(
+        &conn,
+        "collection_paused",
+        if paused { "true" } else { "false" },
+    )
+}
+
+#[tauri::command]
+fn get_collection_paused(db_path: Option<String>) -> Result<bool, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    collection_paused(&conn)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    db_path: String,
+    sample_count: i64,
+    last_sample_ts_ms: Option<i64>,
+    last_sample_age_ms: Option<i64>,
+    // recording is paused from the UI (see `set_collection_paused`)
+    paused: bool,
+    // some sample in the last 24h had remaining_tokens <= 0
+    context_overflow_detected: bool,
+    // the file's SHA-256 differs from the one stored by `get_database_file_hash`; `None` before
+    // the first `get_database_file_hash`, and in the dashboard pack, which doesn't hash
+    external_modification_detected: Option<bool>,
+}
+
+#[tauri::command]
+fn health_check(db_path: Option<String>) -> Result<HealthStatus, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    health_status(&conn, db_path, true)
+}
+
+// Hashing reads the whole file, so polling callers pass `check_hash = false`.
+fn health_status(
+    conn: &Connection,
+    db_path: String,
+    check_hash: bool,
+) -> Result<HealthStatus, String> {
+    let (sample_count, last_sample_ts_ms): (i64, Option<i64>) =
+        timed_query(conn, "SELECT COUNT(*), MAX(ts_ms) FROM samples", [], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })?;
+    let context_overflow_detected: bool = timed_query(
+        conn,
+        r#"
+        SELECT EXISTS(
+          SELECT 1 FROM samples
+          WHERE remaining_tokens IS NOT NULL AND remaining_tokens <= 0 AND ts_ms >= ?1
+        )
+        "#,
+        [now_ms() - DAY_MS],
+        |r| r.get(0),
+    )?;
+    let external_modification_detected = match get_setting(conn, "last_db_hash")? {
+        Some(stored) if check_hash => Some(file_sha256_hex(&db_path)? != stored),
+        _ => None,
+    };
+
+    Ok(HealthStatus {
+        sample_count,
+        last_sample_ts_ms,
+        last_sample_age_ms: last_sample_ts_ms.map(|ts| now_ms() - ts),
+        paused: collection_paused(conn)?,
+        context_overflow_detected,
+        external_modification_detected,
+        db_path,
+    })
+}
+
+const MAX_SESSION_PREFIX_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefixGroup {
+    prefix: String,
+    session_count: i64,
+    total_tokens: Option<i64>,
+    latest_ts_ms: i64,
+}
+
+#[tauri::command]
+fn get_session_key_prefix_groups(
+    prefix_length: usize,
+    db_path: Option<String>,
+) -> Result<Vec<PrefixGroup>, String> {
+    if prefix_length == 0 || prefix_length > MAX_SESSION_PREFIX_LENGTH {
+        return Err(format!(
+            "prefix_length must be between 1 and {}",
+            MAX_SESSION_PREFIX_LENGTH
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut groups: std::collections::BTreeMap<String, PrefixGroup> =
+        std::collections::BTreeMap::new();
+    for s in load_session_totals(&conn)? {
+        // Count characters, not bytes, so multi-byte keys never split mid-character.
+        let prefix: String = s.session_key.chars().take(prefix_length).collect();
+        let g = groups.entry(prefix.clone()).or_insert(PrefixGroup {
+            prefix,
+            session_count: 0,
+            total_tokens: None,
+            latest_ts_ms: s.last_ts_ms,
+        });
+        g.session_count += 1;
+        g.latest_ts_ms = g.latest_ts_ms.max(s.last_ts_ms);
+        if let Some(t) = s.total_tokens {
+            g.total_tokens = Some(g.total_tokens.unwrap_or(0) + t);
+        }
+    }
+
+    Ok(groups.into_values().collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EfficiencyRow {
+    model: Option<String>,
+    total_tokens: i64,
+    estimated_cost_usd: f64,
+    tokens_per_usd: f64,
+}
+
+// Most efficient model first.
+fn efficiency_by_model(
+    conn: &Connection,
+    cost_config: &CostConfig,
+) -> Result<Vec<EfficiencyRow>, String> {
+    // Sessions are attributed to the model of their latest sample.
+    let mut by_model: HashMap<Option<String>, (i64, f64)> = HashMap::new();
+    for s in load_session_totals(conn)? {
+        let cost = cost_config.cost_usd(
+            s.model.as_deref(),
+            s.input_tokens.unwrap_or(0),
+            s.output_tokens.unwrap_or(0),
+        );
+        if let (Some(total), Some(cost)) = (s.total_tokens, cost) {
+            let e = by_model.entry(s.model).or_insert((0, 0.0));
+            e.0 += total;
+            e.1 += cost;
+        }
+    }
+
+    // Unpriced or free models have no meaningful ratio, so they're left out.
+    let mut out: Vec<EfficiencyRow> = by_model
+        .into_iter()
+        .filter(|(_, (_, cost))| *cost > 0.0)
+        .map(|(model, (total_tokens, cost))| EfficiencyRow {
+            model,
+            total_tokens,
+            estimated_cost_usd: cost,
+            tokens_per_usd: total_tokens as f64 / cost,
+        })
+        .collect();
+    out.sort_by(|a, b| b.tokens_per_usd.total_cmp(&a.tokens_per_usd));
+
+    Ok(out)
+}
+
+#[tauri::command]
+fn get_tokens_per_usd(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<EfficiencyRow>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    efficiency_by_model(&conn, &cost_config)
+}
+
+const MIN_TREND_POINTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrendMetric {
+    TotalTokens,
+    PercentUsed,
+    TokensPerS,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendResult {
+    slope_per_ms: f64,
+    r_squared: f64,
+    direction: TrendDirection,
+}
+
+#[tauri::command]
+fn get_trend_slope(
+    metric: TrendMetric,
+    window_ms: i64,
+    db_path: Option<String>,
+) -> Result<Option<TrendResult>, String> {
+    if window_ms <= 0 {
+        return Err("window_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let end = now_ms();
+    let start = end - window_ms;
+    let mut points: Vec<(i64, f64)> = Vec::new();
+    for session in load_samples_by_session_in_range(&conn, None, start, end)? {
+        match metric {
+            TrendMetric::TotalTokens => points.extend(
+                session
+                    .iter()
+                    .filter_map(|s| s.total_tokens.map(|t| (s.ts_ms, t as f64))),
+            ),
+            TrendMetric::PercentUsed => points.extend(
+                session
+                    .iter()
+                    .filter_map(|s| s.percent_used.map(|p| (s.ts_ms, p as f64))),
+            ),
+            TrendMetric::TokensPerS => points.extend(pair_rates(&session)),
+        }
+    }
+    if points.len() < MIN_TREND_POINTS {
+        return Ok(None);
+    }
+
+    // Regress on offsets from the window start; absolute epoch ms would cost precision in the sums.
+    let xy: Vec<(f64, f64)> = points
+        .iter()
+        .map(|&(ts, y)| ((ts - start) as f64, y))
+        .collect();
+    Ok(
+        linear_regression(&xy).map(|(slope, _, r_squared)| TrendResult {
+            slope_per_ms: slope,
+            r_squared,
+            direction: trend_direction(slope, r_squared),
+        }),
+    )
+}
+
+const UNTAGGED_BUCKET: &str = "__untagged__";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCost {
+    tag: String,
+    total_input_tokens: i64,
+    total_output_tokens: i64,
+    estimated_cost_usd: f64,
+    session_count: i64,
+}
+
+// session_key -> tags, from the optional `session_tags` table.
+fn load_session_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut out: HashMap<String, Vec<String>> = HashMap::new();
+    if !table_exists(conn, "session_tags")? {
+        return Ok(out);
+    }
+    let mut stmt = conn
+        .prepare("SELECT session_key, tag FROM session_tags ORDER BY session_key, tag")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (session_key, tag) = row.map_err(|e| e.to_string())?;
+        out.entry(session_key).or_default().push(tag);
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+fn get_cost_by_project(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<ProjectCost>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let tags = load_session_tags(&conn)?;
+    let untagged = vec![UNTAGGED_BUCKET.to_string()];
+    let mut by_tag: HashMap<String, ProjectCost> = HashMap::new();
+    for s in load_session_totals(&conn)? {
+        let input = s.input_tokens.unwrap_or(0);
+        let output = s.output_tokens.unwrap_or(0);
+        // Unpriced models still count towards token totals, just not cost.
+        let cost = cost_config
+            .cost_usd(s.model.as_deref(), input, output)
+            .unwrap_or(0.0);
+        // A session with several tags counts fully towards each of them.
+        for tag in tags.get(&s.session_key).unwrap_or(&untagged) {
+            let p = by_tag.entry(tag.clone()).or_insert(ProjectCost {
+                tag: tag.clone(),
+                total_input_tokens: 0,
+                total_output_tokens: 0,
+                estimated_cost_usd: 0.0,
+                session_count: 0,
+            });
+            p.total_input_tokens += input;
+            p.total_output_tokens += output;
+            p.estimated_cost_usd += cost;
+            p.session_count += 1;
+        }
+    }
+
+    let mut out: Vec<ProjectCost> = by_tag.into_values().collect();
+    out.sort_by(|a, b| {
+        b.estimated_cost_usd
+            .total_cmp(&a.estimated_cost_usd)
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+    Ok(out)
+}
+
+const PULSE_WINDOW_MS: i64 = 60_000;
+const PULSE_IDLE_AFTER_MS: i64 = 30_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActivityStatus {
+    Idle,
+    Active,
+    Burst,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPulse {
+    active_sessions: i64,
+    total_tokens_last_60s: Option<i64>,
+    peak_tokens_per_s_last_60s: Option<f64>,
+    last_sample_age_ms: i64,
+    status: ActivityStatus,
+}
+
+#[tauri::command]
+fn get_recent_activity_pulse(db_path: Option<String>) -> Result<ActivityPulse, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    let last_ts: Option<i64> =
+        timed_query(&conn, "SELECT MAX(ts_ms) FROM samples", [], |r| r.get(0))?;
+    let last_sample_age_ms = now - last_ts.ok_or_else(|| "no samples recorded yet".to_string())?;
+
+    let sessions = load_samples_by_session_in_range(&conn, None, now - PULSE_WINDOW_MS, now)?;
+    let mut total_tokens_last_60s = None;
+    let mut rates = Vec::new();
+    for session in &sessions {
+        if let (Some(f), Some(l)) = (session.first(), session.last()) {
+            if let Some(d) = counter_delta(f.total_tokens, l.total_tokens) {
+                total_tokens_last_60s = Some(total_tokens_last_60s.unwrap_or(0) + d);
+            }
+        }
+        rates.extend(pair_rates(session).into_iter().map(|(_, r)| r));
+    }
+    let peak = rates.iter().copied().reduce(f64::max);
+
+    let status = if last_sample_age_ms > PULSE_IDLE_AFTER_MS {
+        ActivityStatus::Idle
+    } else {
+        match (peak, mean(&rates)) {
+            (Some(p), Some(m)) if m > 0.0 && p > 2.0 * m => ActivityStatus::Burst,
+            _ => ActivityStatus::Active,
+        }
+    };
+
+    Ok(ActivityPulse {
+        active_sessions: sessions.len() as i64,
+        total_tokens_last_60s,
+        peak_tokens_per_s_last_60s: peak,
+        last_sample_age_ms,
+        status,
+    })
+}
+
+const MAX_SMOOTHING_WINDOW: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VelocityPoint {
+    ts_ms: i64,
+    velocity: Option<f64>,
+    acceleration: Option<f64>,
+}
+
+#[tauri::command]
+fn get_token_velocity_change(
+    session_key: String,
+    smoothing_window: usize,
+    db_path: Option<String>,
+) -> Result<Vec<VelocityPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let window = smoothing_window.clamp(1, MAX_SMOOTHING_WINDOW);
+    let samples = load_session_samples(&conn, &session_key)?;
+
+    // Raw rate per sample (None for the first sample and across counter resets),
+    // then a trailing moving average over the last `window` usable rates.
+    let mut raw: Vec<Option<f64>> = vec![None];
+    raw.extend(samples.windows(2).map(|w| pair_rate(&w[0], &w[1])));
+
+    let mut out: Vec<VelocityPoint> = Vec::with_capacity(samples.len());
+    for (i, s) in samples.iter().enumerate() {
+        let start = (i + 1).saturating_sub(window);
+        let recent: Vec<f64> = raw[start..=i].iter().flatten().copied().collect();
+        let velocity = if raw[i].is_some() {
+            mean(&recent)
+        } else {
+            None
+        };
+        let acceleration = match (out.last().and_then(|p| p.velocity), velocity) {
+            (Some(prev), Some(cur)) => Some(cur - prev),
+            _ => None,
+        };
+        out.push(VelocityPoint {
+            ts_ms: s.ts_ms,
+            velocity,
+            acceleration,
+        });
+    }
+    Ok(out)
+}
+
+const MAX_SIMILAR_SESSIONS: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarSession {
+    session_key: String,
+    similarity_score: f64,
+    total_tokens_delta: i64,
+    duration_ms: i64,
+}
+
+// Percent difference of `v` from `target`; a zero target only matches exactly.
+fn pct_diff(v: i64, target: i64) -> f64 {
+    if target == 0 {
+        if v == 0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        ((v - target) as f64 / target as f64).abs() * 100.0
+    }
+}
+
+#[tauri::command]
+fn find_similar_sessions(
+    session_key: String,
+    tolerance_pct: f64,
+    db_path: Option<String>,
+) -> Result<Vec<SimilarSession>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // (key, total tokens, duration) for sessions with a usable token delta.
+    let sessions: Vec<(String, i64, i64)> = load_session_totals(&conn)?
+        .into_iter()
+        .filter_map(|s| {
+            s.total_tokens
+                .map(|t| (s.session_key, t, s.last_ts_ms - s.first_ts_ms))
+        })
+        .collect();
+    let (target_tokens, target_duration) = sessions
+        .iter()
+        .find(|(k, _, _)| *k == session_key)
+        .map(|(_, t, d)| (*t, *d))
+        .ok_or_else(|| format!("no samples for session {}", session_key))?;
+
+    // Normalize both dimensions by their max so neither dominates the distance.
+    let max_tokens = sessions.iter().map(|s| s.1).max().unwrap_or(0).max(1) as f64;
+    let max_duration = sessions.iter().map(|s| s.2).max().unwrap_or(0).max(1) as f64;
+
+    let mut out: Vec<SimilarSession> = sessions
+        .into_iter()
+        .filter(|(k, t, d)| {
+            *k != session_key
+                && pct_diff(*t, target_tokens) <= tolerance_pct
+                && pct_diff(*d, target_duration) <= tolerance_pct
+        })
+        .map(|(k, t, d)| {
+            let dt = (t - target_tokens) as f64 / max_tokens;
+            let dd = (d - target_duration) as f64 / max_duration;
+            let distance = (dt * dt + dd * dd).sqrt();
+            SimilarSession {
+                session_key: k,
+                // distance is at most sqrt(2) after normalization
+                similarity_score: 1.0 - distance / std::f64::consts::SQRT_2,
+                total_tokens_delta: t - target_tokens,
+                duration_ms: d,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.similarity_score
+            .total_cmp(&a.similarity_score)
+            .then_with(|| a.session_key.cmp(&b.session_key))
+    });
+    out.truncate(MAX_SIMILAR_SESSIONS);
+    Ok(out)
+}
+
+#[tauri::command]
+fn compact_session_samples(
+    session_key: String,
+    merge_window_ms: i64,
+    cache: tauri::State<RollupCache>,
+    db_path: Option<String>,
+) -> Result<i64, String> {
+    if merge_window_ms <= 0 {
+        return Err("merge_window_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let mut conn = open_db(&db_path)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let ts: Vec<i64> = {
+        let mut stmt = tx
+            .prepare("SELECT ts_ms FROM samples WHERE session_key = ?1 ORDER BY ts_ms ASC")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([&session_key], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    // A sample followed within the window by another one belongs to the same
+    // burst; only the last sample of each burst survives.
+    let mut deleted = 0i64;
+    {
+        let mut del = tx
+            .prepare("DELETE FROM samples WHERE session_key = ?1 AND ts_ms = ?2")
+            .map_err(|e| e.to_string())?;
+        for w in ts.windows(2) {
+            if w[1] - w[0] <= merge_window_ms {
+                deleted += del
+                    .execute(rusqlite::params![session_key, w[0]])
+                    .map_err(|e| e.to_string())? as i64;
+            }
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    cache.invalidate();
+
+    Ok(deleted)
+}
+
+const FORECAST_HISTORY_DAYS: i64 = 14;
+const MAX_FORECAST_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastPoint {
+    date_label: String,
+    forecasted_tokens: i64,
+    forecasted_cost_usd: f64,
+    confidence_interval_low: f64,
+    confidence_interval_high: f64,
+}
+
+// (total tokens, estimated cost) over the day from `day_start_ms`, summed from per-session deltas.
+fn daily_usage(
+    conn: &Connection,
+    cost_config: &CostConfig,
+    day_start_ms: i64,
+) -> Result<(i64, f64), String> {
+    let mut tokens = 0i64;
+    let mut cost = 0.0;
+    for session in
+        load_samples_by_session_in_range(conn, None, day_start_ms, day_start_ms + DAY_MS - 1)?
+    {
+        let (Some(f), Some(l)) = (session.first(), session.last()) else {
+            continue;
+        };
+        let input = counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0);
+        let output = counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0);
+        tokens += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+        cost += cost_config
+            .cost_usd(l.model.as_deref(), input, output)
+            .unwrap_or(0.0);
+    }
+    Ok((tokens, cost))
+}
+
+#[tauri::command]
+fn get_cost_forecast_series(
+    forecast_days: u32,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<ForecastPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Fit on the last 14 complete UTC days; today is still in progress, so it
+    // is the first forecast day rather than a history point.
+    let today = local_day_start_ms(now_ms(), 0);
+    let mut token_points = Vec::new();
+    let mut cost_points = Vec::new();
+    for i in 0..FORECAST_HISTORY_DAYS {
+        let day = today - (FORECAST_HISTORY_DAYS - i) * DAY_MS;
+        let (tokens, cost) = daily_usage(&conn, &cost_config, day)?;
+        token_points.push((i as f64, tokens as f64));
+        cost_points.push((i as f64, cost));
+    }
+    let (token_slope, token_intercept, _) =
+        linear_regression(&token_points).ok_or_else(|| "not enough history".to_string())?;
+    let (cost_slope, cost_intercept, _) =
+        linear_regression(&cost_points).ok_or_else(|| "not enough history".to_string())?;
+
+    let residuals: Vec<f64> = cost_points
+        .iter()
+        .map(|(x, y)| (y - (cost_slope * x + cost_intercept)).powi(2))
+        .collect();
+    let stddev = mean(&residuals).unwrap_or(0.0).sqrt();
+
+    let out = (0..forecast_days.min(MAX_FORECAST_DAYS) as i64)
+        .map(|k| {
+            let x = (FORECAST_HISTORY_DAYS + k) as f64;
+            let cost = (cost_slope * x + cost_intercept).max(0.0);
+            ForecastPoint {
+                date_label: local_date_label(today + k * DAY_MS, 0),
+                forecasted_tokens: (token_slope * x + token_intercept).max(0.0).round() as i64,
+                forecasted_cost_usd: cost,
+                confidence_interval_low: (cost - stddev).max(0.0),
+                confidence_interval_high: cost + stddev,
+            }
+        })
+        .collect();
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextAlert {
+    session_key: String,
+    model: Option<String>,
+    percent_used: i64,
+    remaining_tokens: Option<i64>,
+    last_ts_ms: i64,
+    age_ms: i64,
+}
+
+#[tauri::command]
+fn get_sessions_approaching_context_limit(
+    threshold_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<ContextAlert>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT s.session_key, s.model, s.percent_used, s.remaining_tokens, s.ts_ms
+            FROM samples s
+            JOIN (
+                SELECT session_key, MAX(ts_ms) AS ts_ms
+                FROM samples
+                WHERE session_key IS NOT NULL
+                GROUP BY session_key
+            ) latest ON latest.session_key = s.session_key AND latest.ts_ms = s.ts_ms
+            WHERE s.percent_used >= ?1
+            ORDER BY s.percent_used DESC, s.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([threshold_pct], |r| {
+            let last_ts_ms: i64 = r.get(4)?;
+            Ok(ContextAlert {
+                session_key: r.get(0)?,
+                model: r.get(1)?,
+                percent_used: r.get(2)?,
+                remaining_tokens: r.get(3)?,
+                last_ts_ms,
+                age_ms: now - last_ts_ms,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+// Runs a sample DELETE in a transaction and records it under the `last_delete` setting.
+fn logged_delete(
+    cache: &RollupCache,
+    db_path: String,
+    confirm: bool,
+    sql: &str,
+    params: impl rusqlite::Params,
+    description: String,
+) -> Result<i64, String> {
+    if !confirm {
+        return Err("refusing to delete samples without confirm = true".to_string());
+    }
+    let mut conn = open_db(&db_path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let deleted = tx.execute(sql, params).map_err(|e| e.to_string())? as i64;
+    prune_orphans(&tx)?;
+    set_setting(
+        &tx,
+        "last_delete",
+        &format!("{} ({} rows)", description, deleted),
+    )?;
+    tx.commit().map_err(|e| e.to_string())?;
+    cache.invalidate();
+    Ok(deleted)
+}
+
+#[tauri::command]
+fn delete_samples_by_model(
+    model: String,
+    confirm: bool,
+    cache: tauri::State<RollupCache>,
+    db_path: Option<String>,
+) -> Result<i64, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let description = format!("delete_samples_by_model model={}", model);
+    logged_delete(
+        &cache,
+        db_path,
+        confirm,
+        "DELETE FROM samples WHERE model = ?1",
+        [model],
+        description,
+    )
+}
+
+#[tauri::command]
+fn delete_samples_before(
+    ts_ms: i64,
+    confirm: bool,
+    cache: tauri::State<RollupCache>,
+    db_path: Option<String>,
+) -> Result<i64, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    logged_delete(
+        &cache,
+        db_path,
+        confirm,
+        "DELETE FROM samples WHERE ts_ms < ?1",
+        [ts_ms],
+        format!("delete_samples_before ts_ms={}", ts_ms),
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioPoint {
+    ts_ms: i64,
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    ratio: Option<f64>,
+}
+
+#[tauri::command]
+fn get_input_output_ratio_series(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<RatioPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Cumulative counters, not deltas: the ratio describes the conversation so far.
+    let out = load_session_samples(&conn, &session_key)?
+        .into_iter()
+        .map(|s| RatioPoint {
+            ts_ms: s.ts_ms,
+            input_tokens: s.input_tokens,
+            output_tokens: s.output_tokens,
+            ratio: match (s.input_tokens, s.output_tokens) {
+                (Some(i), Some(o)) if i != 0 => Some(o as f64 / i as f64),
+                _ => None,
+            },
+        })
+        .collect();
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEconomyReport {
+    total_tokens_all_time: i64,
+    total_cost_usd: f64,
+    tokens_per_usd: Option<f64>,
+    best_model_by_efficiency: Option<String>,
+    worst_model_by_efficiency: Option<String>,
+    peak_throughput_tokens_per_s: Option<f64>,
+    avg_context_utilization_pct: Option<f64>,
+    total_sessions: i64,
+    avg_session_cost_usd: Option<f64>,
+}
+
+#[tauri::command]
+fn get_token_economy_report(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<TokenEconomyReport, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let totals = load_session_totals(&conn)?;
+    let total_tokens_all_time: i64 = totals.iter().filter_map(|s| s.total_tokens).sum();
+    // Unpriced models count towards tokens but not cost, as in get_cost_by_project.
+    let total_cost_usd: f64 = totals
+        .iter()
+        .filter_map(|s| {
+            cost_config.cost_usd(
+                s.model.as_deref(),
+                s.input_tokens.unwrap_or(0),
+                s.output_tokens.unwrap_or(0),
+            )
+        })
+        .sum();
+
+    let efficiency = efficiency_by_model(&conn, &cost_config)?;
+    let peak_throughput_tokens_per_s = load_samples_by_session(&conn)?
+        .iter()
+        .flat_map(|s| pair_rates(s))
+        .map(|(_, r)| r)
+        .reduce(f64::max);
+    let avg_context_utilization_pct: Option<f64> =
+        timed_query(&conn, "SELECT AVG(percent_used) FROM samples", [], |r| {
+            r.get(0)
+        })?;
+
+    let total_sessions = totals.len() as i64;
+    Ok(TokenEconomyReport {
+        total_tokens_all_time,
+        total_cost_usd,
+        tokens_per_usd: (total_cost_usd > 0.0)
+            .then(|| total_tokens_all_time as f64 / total_cost_usd),
+        best_model_by_efficiency: efficiency.first().and_then(|e| e.model.clone()),
+        worst_model_by_efficiency: efficiency.last().and_then(|e| e.model.clone()),
+        peak_throughput_tokens_per_s,
+        avg_context_utilization_pct,
+        total_sessions,
+        avg_session_cost_usd: (total_sessions > 0).then(|| total_cost_usd / total_sessions as f64),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnInfo {
+    cid: i64,
+    name: String,
+    #[serde(rename = "type")]
+    type_: String,
+    notnull: bool,
+    default_value: Option<String>,
+    pk: bool,
+}
+
+fn samples_columns(conn: &Connection) -> Result<Vec<ColumnInfo>, String> {
+    let mut stmt = conn
+        .prepare("PRAGMA table_info('samples')")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(ColumnInfo {
+                cid: r.get(0)?,
+                name: r.get(1)?,
+                type_: r.get(2)?,
+                notnull: r.get::<_, i64>(3)? != 0,
+                default_value: r.get(4)?,
+                pk: r.get::<_, i64>(5)? != 0,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplesSchema {
+    columns: Vec<ColumnInfo>,
+    // SAMPLE_COLUMNS entries the table lacks; the app's own sample queries fail until it has them
+    missing_columns: Vec<String>,
+}
+
+#[tauri::command]
+fn get_samples_schema_columns(db_path: Option<String>) -> Result<SamplesSchema, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let columns = samples_columns(&conn)?;
+    let missing_columns = SAMPLE_COLUMNS
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !columns.iter().any(|col| col.name == *c))
+        .map(str::to_string)
+        .collect();
+    Ok(SamplesSchema {
+        columns,
+        missing_columns,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCostBreakdown {
+    session_key: String,
+    input_tokens: i64,
+    output_tokens: i64,
+    input_cost_usd: f64,
+    output_cost_usd: f64,
+    total_cost_usd: f64,
+    input_cost_pct: f64,
+    output_cost_usd_pct: f64,
+}
+
+#[tauri::command]
+fn get_session_cost_breakdown(
+    session_key: String,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<SessionCostBreakdown, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return Err(format!("no samples for session {}", session_key)),
+    };
+    let price = cost_config
+        .price_for(last.model.as_deref())
+        .ok_or_else(|| {
+            format!(
+                "no price configured for model {}",
+                last.model.as_deref().unwrap_or("(unknown)")
+            )
+        })?;
+
+    Ok(cost_breakdown(
+        session_key,
+        price,
+        counter_delta(first.input_tokens, last.input_tokens).unwrap_or(0),
+        counter_delta(first.output_tokens, last.output_tokens).unwrap_or(0),
+    ))
+}
+
+fn cost_breakdown(
+    session_key: String,
+    price: &ModelPrice,
+    input_tokens: i64,
+    output_tokens: i64,
+) -> SessionCostBreakdown {
+    let input_cost_usd = input_tokens as f64 * price.input_usd_per_mtok / 1_000_000.0;
+    let output_cost_usd = output_tokens as f64 * price.output_usd_per_mtok / 1_000_000.0;
+    let total_cost_usd = input_cost_usd + output_cost_usd;
+    let pct = |c: f64| {
+        if total_cost_usd > 0.0 {
+            c / total_cost_usd * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    SessionCostBreakdown {
+        session_key,
+        input_tokens,
+        output_tokens,
+        input_cost_usd,
+        output_cost_usd,
+        total_cost_usd,
+        input_cost_pct: pct(input_cost_usd),
+        output_cost_usd_pct: pct(output_cost_usd),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LongestIdle {
+    session_key: String,
+    idle_start_ms: i64,
+    idle_end_ms: i64,
+    idle_duration_ms: i64,
+}
+
+#[tauri::command]
+fn get_longest_idle_session(
+    min_idle_ms: i64,
+    db_path: Option<String>,
+) -> Result<Option<LongestIdle>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH gaps AS (
+                SELECT session_key,
+                       LAG(ts_ms) OVER (PARTITION BY session_key ORDER BY ts_ms) AS prev_ts,
+                       ts_ms
+                FROM samples
+                WHERE session_key IS NOT NULL
+            )
+            SELECT session_key, prev_ts, ts_ms
+            FROM gaps
+            WHERE prev_ts IS NOT NULL AND ts_ms - prev_ts > ?1
+            ORDER BY ts_ms - prev_ts DESC, session_key ASC
+            LIMIT 1
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query_map([min_idle_ms], |r| {
+            let idle_start_ms: i64 = r.get(1)?;
+            let idle_end_ms: i64 = r.get(2)?;
+            Ok(LongestIdle {
+                session_key: r.get(0)?,
+                idle_start_ms,
+                idle_end_ms,
+                idle_duration_ms: idle_end_ms - idle_start_ms,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.next().transpose().map_err(|e| e.to_string())
+}
+
+const DEFAULT_STREAM_INTERVAL_MS: u64 = 1000;
+
+// Stop flag of the running live-metrics stream, if any.
+#[derive(Default)]
+pub struct LiveStream {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStatus {
+    last_sample_age_ms: i64,
+}
+
+// Emits `live-metrics-update` every `interval_ms`. With `stale_threshold_ms`, updates are
+// held back (bracketed by `stream-stalled` / `stream-resumed`) while the newest sample is
+// older than the threshold, so the UI doesn't show old rates as current.
+#[tauri::command]
+fn start_live_metrics_stream(
+    app: tauri::AppHandle,
+    interval_ms: Option<u64>,
+    stale_threshold_ms: Option<i64>,
+    stream: tauri::State<LiveStream>,
+    db_path: Option<String>,
+) -> Result<(), String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some(prev) = stream
+        .stop
+        .lock()
+        .map_err(|e| e.to_string())?
+        .replace(stop.clone())
+    {
+        prev.store(true, Ordering::Relaxed);
+    }
+
+    let interval =
+        std::time::Duration::from_millis(interval_ms.unwrap_or(DEFAULT_STREAM_INTERVAL_MS).max(1));
+    std::thread::spawn(move || {
+        let mut stalled = false;
+        while !stop.load(Ordering::Relaxed) {
+            // No samples yet is not an error worth surfacing; just try again next tick.
+            if let Ok(m) = get_live_metrics(None, None, None, db_path.clone(), app.state()) {
+                let last_sample_age_ms = now_ms() - m.ts_ms;
+                let stale = stale_threshold_ms.is_some_and(|t| last_sample_age_ms > t);
+                if stale != stalled {
+                    let event = if stale {
+                        "stream-stalled"
+                    } else {
+                        "stream-resumed"
+                    };
+                    let _ = app.emit(event, StreamStatus { last_sample_age_ms });
+                    stalled = stale;
+                }
+                if !stalled {
+                    let _ = app.emit("live-metrics-update", m);
+                }
+            }
+            std::thread::sleep(interval);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+fn stop_live_metrics_stream(stream: tauri::State<LiveStream>) -> Result<(), String> {
+    if let Some(stop) = stream.stop.lock().map_err(|e| e.to_string())?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapStats {
+    max_concurrent_sessions: i64,
+    total_overlap_ms: i64,
+    concurrent_periods: Vec<ConcurrentPeriod>,
+}
+
+#[tauri::command]
+fn get_session_overlap_stats(db_path: Option<String>) -> Result<OverlapStats, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let spans = load_session_spans(&conn)?;
+    // (ts, is_start, span index); at equal timestamps ends sort first so touching spans don't overlap.
+    let mut events: Vec<(i64, bool, usize)> = spans
+        .iter()
+        .enumerate()
+        // single-sample sessions have no duration to overlap with
+        .filter(|(_, (_, start, end))| end > start)
+        .flat_map(|(i, (_, start, end))| [(*start, true, i), (*end, false, i)])
+        .collect();
+    events.sort();
+
+    let mut active: Vec<usize> = Vec::new();
+    let mut max_concurrent_sessions = 0i64;
+    let mut total_overlap_ms = 0i64;
+    let mut concurrent_periods = Vec::new();
+    let mut prev_ts: Option<i64> = None;
+    for (ts, is_start, i) in events {
+        // The segment since the previous event had exactly `active` running.
+        if let Some(p) = prev_ts {
+            if active.len() >= 2 && ts > p {
+                total_overlap_ms += ts - p;
+                if concurrent_periods.len() < MAX_CONCURRENT_PERIODS {
+                    let mut session_keys: Vec<String> =
+                        active.iter().map(|&a| spans[a].0.clone()).collect();
+                    session_keys.sort();
+                    concurrent_periods.push(ConcurrentPeriod {
+                        start_ms: p,
+                        end_ms: ts,
+                        session_keys,
+                        overlap_ms: ts - p,
+                    });
+                }
+            }
+        }
+        if is_start {
+            active.push(i);
+            max_concurrent_sessions = max_concurrent_sessions.max(active.len() as i64);
+        } else {
+            active.retain(|&a| a != i);
+        }
+        prev_ts = Some(ts);
+    }
+
+    Ok(OverlapStats {
+        max_concurrent_sessions,
+        total_overlap_ms,
+        concurrent_periods,
+    })
+}
+
+const ANOMALY_MIN_BYTES_PER_TOKEN: f64 = 1000.0;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkAnomaly {
+    ts_ms: i64,
+    session_key: Option<String>,
+    net_rx_bytes_per_s: f64,
+    tokens_per_s: Option<f64>,
+    bytes_per_token: Option<f64>,
+}
+
+#[tauri::command]
+fn get_net_rx_anomalies(
+    threshold_bytes_per_s: f64,
+    db_path: Option<String>,
+) -> Result<Vec<NetworkAnomaly>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        for w in session.windows(2) {
+            let (a, b) = (&w[0], &w[1]);
+            let dt_s = (b.ts_ms - a.ts_ms) as f64 / 1000.0;
+            let Some(rx_rate) = counter_rate(a.net_rx_bytes, b.net_rx_bytes, dt_s) else {
+                continue;
+            };
+            // Text responses stay well under ~1KB of traffic per token.
+            let bytes_per_token = match (
+                counter_delta(a.net_rx_bytes, b.net_rx_bytes),
+                counter_delta(a.total_tokens, b.total_tokens),
+            ) {
+                (Some(bytes), Some(tokens)) if tokens > 0 => Some(bytes as f64 / tokens as f64),
+                _ => None,
+            };
+            if rx_rate > threshold_bytes_per_s
+                && bytes_per_token.is_some_and(|bpt| bpt > ANOMALY_MIN_BYTES_PER_TOKEN)
+            {
+                out.push(NetworkAnomaly {
+                    ts_ms: b.ts_ms,
+                    session_key: b.session_key.clone(),
+                    net_rx_bytes_per_s: rx_rate,
+                    tokens_per_s: pair_rate(a, b),
+                    bytes_per_token,
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| b.net_rx_bytes_per_s.total_cmp(&a.net_rx_bytes_per_s));
+
+    Ok(out)
+}
+
+/// Output tokens produced per token of context the session used:
+/// `output_tokens delta / max(context_tokens)` over the session's samples.
+///
+/// The score is usually in `[0, 1]`: near 0 means a lot of context was consumed to
+/// generate little output, near 1 means most of the context went into output.
+/// It can exceed 1 when a session is compacted and keeps producing output.
+/// Returns `None` when the session never reported `context_tokens` (or only zeros).
+#[tauri::command]
+fn get_context_efficiency_score(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return Err(format!("no samples for session {}", session_key)),
+    };
+    let max_context = samples.iter().filter_map(|s| s.context_tokens).max();
+    let output = counter_delta(first.output_tokens, last.output_tokens);
+
+    Ok(match (output, max_context) {
+        (Some(o), Some(c)) if c > 0 => Some(o as f64 / c as f64),
+        _ => None,
+    })
+}
+
+const MAX_HISTOGRAM_BUCKETS: u32 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    lower: f64,
+    upper: f64,
+    count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaHistogram {
+    buckets: Vec<HistogramBucket>,
+    min: Option<f64>,
+    max: Option<f64>,
+    total_count: i64,
+    zero_delta_count: i64,
+}
+
+// Equal-width buckets spanning [min, max]; the last bucket includes `max`.
+fn histogram_buckets(values: &[f64], bucket_count: u32) -> Vec<HistogramBucket> {
+    let (Some(min), Some(max)) = (
+        values.iter().copied().reduce(f64::min),
+        values.iter().copied().reduce(f64::max),
+    ) else {
+        return Vec::new();
+    };
+    let n = bucket_count.clamp(1, MAX_HISTOGRAM_BUCKETS) as usize;
+    let width = (max - min) / n as f64;
+    let mut buckets: Vec<HistogramBucket> = (0..n)
+        .map(|i| HistogramBucket {
+            lower: min + width * i as f64,
+            upper: if i + 1 == n {
+                max
+            } else {
+                min + width * (i + 1) as f64
+            },
+            count: 0,
+        })
+        .collect();
+    for v in values {
+        let i = if width > 0.0 {
+            (((v - min) / width) as usize).min(n - 1)
+        } else {
+            0
+        };
+        buckets[i].count += 1;
+    }
+    buckets
+}
+
+#[tauri::command]
+fn get_token_delta_distribution(
+    session_key: Option<String>,
+    start_ms: i64,
+    end_ms: i64,
+    bucket_count: u32,
+    db_path: Option<String>,
+) -> Result<DeltaHistogram, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut deltas = Vec::new();
+    let mut zero_delta_count = 0i64;
+    for session in
+        load_samples_by_session_in_range(&conn, session_key.as_deref(), start_ms, end_ms)?
+    {
+        for w in session.windows(2) {
+            match counter_delta(w[0].total_tokens, w[1].total_tokens) {
+                Some(0) => zero_delta_count += 1,
+                Some(d) => deltas.push(d as f64),
+                None => {}
+            }
+        }
+    }
+
+    // Idle intervals would swamp the buckets, so they're only counted in zero_delta_count.
+    Ok(DeltaHistogram {
+        buckets: histogram_buckets(&deltas, bucket_count),
+        min: deltas.iter().copied().reduce(f64::min),
+        max: deltas.iter().copied().reduce(f64::max),
+        total_count: deltas.len() as i64,
+        zero_delta_count,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedPath {
+    path: String,
+    source: PathSource,
+    exists: bool,
+    readable: bool,
+    writable: bool,
+}
+
+#[tauri::command]
+fn get_db_path_resolved(db_path: Option<String>) -> Result<ResolvedPath, String> {
+    let (path, source) = resolve_db_path(db_path);
+    let exists = std::fs::metadata(&path).is_ok();
+    let readable = std::fs::File::open(&path).is_ok();
+    // Opening for write without truncate/create leaves the file untouched.
+    let writable = std::fs::OpenOptions::new().write(true).open(&path).is_ok();
+    Ok(ResolvedPath {
+        path,
+        source,
+        exists,
+        readable,
+        writable,
+    })
+}
+
+// Persist a custom DB path to the config file. Refused while CLAWMONITOR_DB is set, since the
+// environment variable would keep shadowing the persisted path.
+#[tauri::command]
+fn set_db_path_persistent(new_path: String) -> Result<(), String> {
+    if let Some(env_path) = env_db_path() {
+        return Err(format!(
+            "CLAWMONITOR_DB is set ({}) and overrides the config file; unset it to persist a db path",
+            env_path
+        ));
+    }
+    let new_path = new_path.trim().to_string();
+    if new_path.is_empty() {
+        return Err("db path must not be empty".to_string());
+    }
+    // config_db_path() reads the value back verbatim, so there is no escaping.
+    if new_path.contains(['"', '\n', '\r']) {
+        return Err("db path must not contain quotes or newlines".to_string());
+    }
+    let path = std::path::Path::new(&new_path);
+    if !path.is_absolute() {
+        return Err(format!("db path must be absolute: {}", new_path));
+    }
+    if path.is_dir() {
+        return Err(format!("db path is a directory: {}", new_path));
+    }
+    if !path.parent().is_some_and(|dir| dir.is_dir()) {
+        return Err(format!("parent directory does not exist: {}", new_path));
+    }
+
+    write_config_db_path(&new_path)?;
+    if let Ok(mut db_path) = AppConfig::get().db_path.lock() {
+        *db_path = Some(new_path);
+    }
+    Ok(())
+}
+
+const MIN_TREND_WINDOW: usize = 2;
+const MAX_TREND_WINDOW: usize = 50;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendPoint {
+    ts_ms: i64,
+    raw_value: Option<i64>,
+    moving_avg: Option<f64>,
+}
+
+// Trailing moving average of one cumulative counter; early points average what's available.
+fn counter_trend(
+    samples: &[SampleRow],
+    window: usize,
+    value: fn(&SampleRow) -> Option<i64>,
+) -> Vec<TrendPoint> {
+    let window = window.clamp(MIN_TREND_WINDOW, MAX_TREND_WINDOW);
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let start = (i + 1).saturating_sub(window);
+            let recent: Vec<f64> = samples[start..=i]
+                .iter()
+                .filter_map(|r| value(r).map(|v| v as f64))
+                .collect();
+            TrendPoint {
+                ts_ms: s.ts_ms,
+                raw_value: value(s),
+                moving_avg: mean(&recent),
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+fn get_input_token_trend(
+    session_key: String,
+    window: usize,
+    db_path: Option<String>,
+) -> Result<Vec<TrendPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    let samples = load_session_samples(&conn, &session_key)?;
+    Ok(counter_trend(&samples, window, |s| s.input_tokens))
+}
+
+#[tauri::command]
+fn get_output_token_trend(
+    session_key: String,
+    window: usize,
+    db_path: Option<String>,
+) -> Result<Vec<TrendPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    let samples = load_session_samples(&conn, &session_key)?;
+    Ok(counter_trend(&samples, window, |s| s.output_tokens))
+}
+
+#[tauri::command]
+fn get_samples_between_annotations(
+    session_key: String,
+    start_annotation_ts_ms: i64,
+    end_annotation_ts_ms: i64,
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    if end_annotation_ts_ms <= start_annotation_ts_ms {
+        return Err("end annotation must come after the start annotation".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let annotations = load_annotations(&conn, &session_key)?;
+    for ts in [start_annotation_ts_ms, end_annotation_ts_ms] {
+        if !annotations.iter().any(|a| a.ts_ms == ts) {
+            return Err(format!(
+                "no annotation at {} for session {}",
+                ts, session_key
+            ));
+        }
+    }
+
+    Ok(load_samples_by_session_in_range(
+        &conn,
+        Some(&session_key),
+        start_annotation_ts_ms,
+        end_annotation_ts_ms,
+    )?
+    .into_iter()
+    .flatten()
+    .collect())
+}
+
+const MAX_DAYS_BACK: u32 = 366;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySessionCount {
+    date_label: String,
+    unique_sessions: i64,
+    new_sessions: i64,
+}
+
+#[tauri::command]
+fn get_unique_session_count_by_day(
+    days_back: u32,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<DailySessionCount>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Days are numbered in local time, as in get_session_activity_grid; today is the last one.
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let days = days_back.min(MAX_DAYS_BACK) as i64;
+    let today = (now_ms() + offset_ms).div_euclid(DAY_MS);
+    let first_day = today - days + 1;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH firsts AS (
+                SELECT session_key, MIN(ts_ms) AS first_ts
+                FROM samples
+                WHERE session_key IS NOT NULL
+                GROUP BY session_key
+            )
+            SELECT (s.ts_ms + ?1) / ?2 AS day,
+                   COUNT(DISTINCT s.session_key),
+                   COUNT(DISTINCT CASE WHEN (f.first_ts + ?1) / ?2 = (s.ts_ms + ?1) / ?2
+                                       THEN s.session_key END)
+            FROM samples s
+            JOIN firsts f ON f.session_key = s.session_key
+            WHERE s.ts_ms >= ?3
+            GROUP BY day
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([offset_ms, DAY_MS, first_day * DAY_MS - offset_ms], |r| {
+            Ok((r.get::<_, i64>(0)?, (r.get(1)?, r.get(2)?)))
+        })
+        .map_err(|e| e.to_string())?;
+    let counts: HashMap<i64, (i64, i64)> =
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+
+    Ok((first_day..=today)
+        .map(|day| {
+            let (unique_sessions, new_sessions) = counts.get(&day).copied().unwrap_or((0, 0));
+            DailySessionCount {
+                date_label: local_date_label(day * DAY_MS - offset_ms, tz_offset_minutes),
+                unique_sessions,
+                new_sessions,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxAnomaly {
+    ts_ms: i64,
+    session_key: Option<String>,
+    net_tx_bytes_per_s: f64,
+    z_score: f64,
+}
+
+#[tauri::command]
+fn get_network_tx_anomalies(
+    z_threshold: f64,
+    db_path: Option<String>,
+) -> Result<Vec<TxAnomaly>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // One global population: a large prompt looks the same whichever session sends it.
+    let mut rates: Vec<(i64, Option<String>, f64)> = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        for w in session.windows(2) {
+            let dt_s = (w[1].ts_ms - w[0].ts_ms) as f64 / 1000.0;
+            if let Some(r) = counter_rate(w[0].net_tx_bytes, w[1].net_tx_bytes, dt_s) {
+                rates.push((w[1].ts_ms, w[1].session_key.clone(), r));
+            }
+        }
+    }
+    let values: Vec<f64> = rates.iter().map(|(_, _, r)| *r).collect();
+    let (Some(m), Some(sd)) = (mean(&values), std_dev(&values)) else {
+        return Ok(Vec::new());
+    };
+    if sd == 0.0 {
+        return Ok(Vec::new());
+    }
+
+    // Only unusually large payloads matter, so the test is one-sided.
+    let mut out: Vec<TxAnomaly> = rates
+        .into_iter()
+        .map(|(ts_ms, session_key, r)| TxAnomaly {
+            ts_ms,
+            session_key,
+            net_tx_bytes_per_s: r,
+            z_score: (r - m) / sd,
+        })
+        .filter(|a| a.z_score > z_threshold)
+        .collect();
+    out.sort_by(|a, b| b.z_score.total_cmp(&a.z_score));
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CumulativeTotals {
+    input_tokens: i64,
+    output_tokens: i64,
+    total_tokens: i64,
+    session_count: i64,
+    // only with a cost config
+    estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    live: Option<LiveMetrics>,
+    rollup_1d: Rollup,
+    rollup_7d: Rollup,
+    cumulative: CumulativeTotals,
+    active_session_count: i64,
+    budget: Option<BudgetRemaining>,
+}
+
+// Everything the main dashboard needs in one round trip. `budget` is filled when a
+// `daily_quota_tokens` is given, with days starting at local midnight (UTC without an offset).
+#[tauri::command]
+fn get_combined_usage_summary(
+    cost_config: Option<CostConfig>,
+    daily_quota_tokens: Option<i64>,
+    tz_offset_minutes: Option<i32>,
+    db_path: Option<String>,
+) -> Result<UsageSummary, String> {
+    if daily_quota_tokens.is_some_and(|q| q <= 0) {
+        return Err("daily_quota_tokens must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    let totals = load_session_totals(&conn)?;
+    let cumulative = CumulativeTotals {
+        input_tokens: totals.iter().filter_map(|s| s.input_tokens).sum(),
+        output_tokens: totals.iter().filter_map(|s| s.output_tokens).sum(),
+        total_tokens: totals.iter().filter_map(|s| s.total_tokens).sum(),
+        session_count: totals.len() as i64,
+        estimated_cost_usd: cost_config.as_ref().map(|c| {
+            totals
+                .iter()
+                .filter_map(|s| {
+                    c.cost_usd(
+                        s.model.as_deref(),
+                        s.input_tokens.unwrap_or(0),
+                        s.output_tokens.unwrap_or(0),
+                    )
+                })
+                .sum()
+        }),
+    };
+    let active_session_count = totals
+        .iter()
+        .filter(|s| now - s.last_ts_ms <= PULSE_WINDOW_MS)
+        .count() as i64;
+
+    let budget = daily_quota_tokens
+        .map(|quota| remaining_budget(&conn, quota, tz_offset_minutes.unwrap_or(0)))
+        .transpose()?;
+
+    Ok(UsageSummary {
+        // An empty database has no live sample; the rest still renders.
+        live: live_metrics(&conn).ok(),
+        rollup_1d: labeled_rollup(&conn, "1d", now - DAY_MS, now),
+        rollup_7d: labeled_rollup(&conn, "7d", now - 7 * DAY_MS, now),
+        cumulative,
+        active_session_count,
+        budget,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertMetric {
+    PercentUsed,
+    TokensPerS,
+    NetRxBytesPerS,
+    NetTxBytesPerS,
+}
+
+impl AlertMetric {
+    fn name(self) -> &'static str {
+        match self {
+            AlertMetric::PercentUsed => "percent_used",
+            AlertMetric::TokensPerS => "tokens_per_s",
+            AlertMetric::NetRxBytesPerS => "net_rx_bytes_per_s",
+            AlertMetric::NetTxBytesPerS => "net_tx_bytes_per_s",
+        }
+    }
+
+    fn value(self, m: &LiveMetrics) -> Option<f64> {
+        match self {
+            AlertMetric::PercentUsed => m.percent_used.map(|p| p as f64),
+            AlertMetric::TokensPerS => m.tokens_per_s,
+            AlertMetric::NetRxBytesPerS => m.net_rx_bytes_per_s,
+            AlertMetric::NetTxBytesPerS => m.net_tx_bytes_per_s,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    metric: AlertMetric,
+    threshold: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRecord {
+    id: i64,
+    ts_ms: i64,
+    metric: String,
+    threshold: f64,
+    value: f64,
+    message: String,
+}
+
+// Evaluates `rules` against the latest live metrics; every rule at or over its
+// threshold fires and is recorded in alert_history.
+#[tauri::command]
+fn check_alerts(
+    rules: Vec<AlertRule>,
+    db_path: Option<String>,
+) -> Result<Vec<AlertRecord>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+
+    let live = live_metrics(&conn)?;
+    let mut fired = Vec::new();
+    for rule in rules {
+        let Some(value) = rule.metric.value(&live) else {
+            continue;
+        };
+        if value < rule.threshold {
+            continue;
+        }
+        let metric = rule.metric.name().to_string();
+        let message = format!(
+            "{} is {:.1} (threshold {:.1})",
+            metric, value, rule.threshold
+        );
+        conn.execute(
+            "INSERT INTO alert_history (ts_ms, metric, threshold, value, message) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![live.ts_ms, metric, rule.threshold, value, message],
+        )
+        .map_err(|e| e.to_string())?;
+        fired.push(AlertRecord {
+            id: conn.last_insert_rowid(),
+            ts_ms: live.ts_ms,
+            metric,
+            threshold: rule.threshold,
+            value,
+            message,
+        });
+    }
+    Ok(fired)
+}
+
+#[tauri::command]
+fn get_alert_history(
+    start_ms: i64,
+    end_ms: i64,
+    db_path: Option<String>,
+) -> Result<Vec<AlertRecord>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    load_alerts(&conn, start_ms, end_ms)
+}
+
+fn load_alerts(conn: &Connection, start_ms: i64, end_ms: i64) -> Result<Vec<AlertRecord>, String> {
+    if !table_exists(conn, "alert_history")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, ts_ms, metric, threshold, value, message
+            FROM alert_history
+            WHERE ts_ms >= ?1 AND ts_ms <= ?2
+            ORDER BY ts_ms ASC, id ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([start_ms, end_ms], |r| {
+            Ok(AlertRecord {
+                id: r.get(0)?,
+                ts_ms: r.get(1)?,
+                metric: r.get(2)?,
+                threshold: r.get(3)?,
+                value: r.get(4)?,
+                message: r.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn clear_alert_history(db_path: Option<String>) -> Result<i64, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+
+    let deleted = conn
+        .execute("DELETE FROM alert_history", [])
+        .map_err(|e| e.to_string())?;
+    Ok(deleted as i64)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionGap {
+    session_key: String,
+    gap_start_ms: i64,
+    gap_end_ms: i64,
+    gap_ms: i64,
+    expected_ms: i64,
+}
+
+#[tauri::command]
+fn get_sample_collection_gaps(
+    expected_interval_ms: i64,
+    multiplier: f64,
+    db_path: Option<String>,
+) -> Result<Vec<CollectionGap>, String> {
+    if expected_interval_ms <= 0 {
+        return Err("expected_interval_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let min_gap_ms = multiplier * expected_interval_ms as f64;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH gaps AS (
+                SELECT session_key,
+                       LAG(ts_ms) OVER (PARTITION BY session_key ORDER BY ts_ms) AS prev_ts,
+                       ts_ms
+                FROM samples
+                WHERE session_key IS NOT NULL AND ts_ms >= ?1
+            )
+            SELECT session_key, prev_ts, ts_ms
+            FROM gaps
+            WHERE prev_ts IS NOT NULL AND ts_ms - prev_ts > ?2
+            ORDER BY ts_ms - prev_ts DESC, session_key ASC, ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![now_ms() - DAY_MS, min_gap_ms], |r| {
+            let gap_start_ms: i64 = r.get(1)?;
+            let gap_end_ms: i64 = r.get(2)?;
+            Ok(CollectionGap {
+                session_key: r.get(0)?,
+                gap_start_ms,
+                gap_end_ms,
+                gap_ms: gap_end_ms - gap_start_ms,
+                expected_ms: expected_interval_ms,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeakSession {
+    session_key: String,
+    model: Option<String>,
+    peak_tokens_per_s: f64,
+    // the peak interval runs from peak_ts_ms to peak_sample_ts_ms
+    peak_ts_ms: i64,
+    peak_sample_ts_ms: i64,
+    duration_ms: i64,
+}
+
+#[tauri::command]
+fn get_peak_session(db_path: Option<String>) -> Result<Option<PeakSession>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut best: Option<PeakSession> = None;
+    for session in load_samples_by_session(&conn)? {
+        let (Some(first), Some(last)) = (session.first(), session.last()) else {
+            continue;
+        };
+        for w in session.windows(2) {
+            let Some(rate) = pair_rate(&w[0], &w[1]) else {
+                continue;
+            };
+            if best.as_ref().is_none_or(|b| rate > b.peak_tokens_per_s) {
+                best = Some(PeakSession {
+                    session_key: w[1].session_key.clone().unwrap_or_default(),
+                    model: w[1].model.clone(),
+                    peak_tokens_per_s: rate,
+                    peak_ts_ms: w[0].ts_ms,
+                    peak_sample_ts_ms: w[1].ts_ms,
+                    duration_ms: last.ts_ms - first.ts_ms,
+                });
+            }
+        }
+    }
+    Ok(best)
+}
+
+const SATURATION_THRESHOLDS: [i64; 6] = [50, 75, 90, 95, 99, 100];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaturationEvent {
+    session_key: String,
+    model: Option<String>,
+    ts_ms: i64,
+    percent_used: i64,
+    was_first_crossing: bool,
+}
+
+#[tauri::command]
+fn get_context_saturation_events(
+    threshold_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<SaturationEvent>, String> {
+    if !SATURATION_THRESHOLDS.contains(&threshold_pct) {
+        return Err(format!(
+            "threshold_pct must be one of {:?}",
+            SATURATION_THRESHOLDS
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        out.extend(saturation_crossings(&session, threshold_pct));
+    }
+    out.sort_by_key(|e| e.ts_ms);
+
+    Ok(out)
+}
+
+fn saturation_crossings(session: &[SampleRow], threshold_pct: i64) -> Vec<SaturationEvent> {
+    let mut out = Vec::new();
+    let mut crossed = false;
+    for w in session.windows(2) {
+        // A crossing goes from below the threshold to at/above it; a session that
+        // starts above it (or drops back after compaction) only counts on the way up.
+        if let (Some(prev), Some(cur)) = (w[0].percent_used, w[1].percent_used) {
+            if prev < threshold_pct && cur >= threshold_pct {
+                out.push(SaturationEvent {
+                    session_key: w[1].session_key.clone().unwrap_or_default(),
+                    model: w[1].model.clone(),
+                    ts_ms: w[1].ts_ms,
+                    percent_used: cur,
+                    was_first_crossing: !crossed,
+                });
+                crossed = true;
+            }
+        }
+    }
+    out
+}
+
+const MAX_AUTOCORR_LAG: usize = 50;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutocorrPoint {
+    lag: usize,
+    correlation: f64,
+}
+
+#[tauri::command]
+fn get_token_rate_autocorrelation(
+    session_key: String,
+    max_lag: usize,
+    db_path: Option<String>,
+) -> Result<Vec<AutocorrPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let rates: Vec<f64> = pair_rates(&samples).into_iter().map(|(_, r)| r).collect();
+    // Every lag needs at least as many overlapping pairs as the lag itself.
+    let max_lag = max_lag.min(MAX_AUTOCORR_LAG).min(rates.len() / 2);
+    if max_lag == 0 {
+        return Err(format!(
+            "not enough rate samples for session {} (have {})",
+            session_key,
+            rates.len()
+        ));
+    }
+
+    // A constant stretch has no defined correlation; report it as uncorrelated.
+    Ok((1..=max_lag)
+        .map(|lag| AutocorrPoint {
+            lag,
+            correlation: pearson(&rates[..rates.len() - lag], &rates[lag..]).unwrap_or(0.0),
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextWindowSize {
+    model: Option<String>,
+    context_tokens: i64,
+    session_count: i64,
+    first_seen_ms: i64,
+}
+
+#[tauri::command]
+fn get_context_window_sizes(db_path: Option<String>) -> Result<Vec<ContextWindowSize>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT model, context_tokens, COUNT(DISTINCT session_key), MIN(ts_ms)
+            FROM samples
+            WHERE context_tokens IS NOT NULL
+            GROUP BY model, context_tokens
+            ORDER BY model ASC, context_tokens ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(ContextWindowSize {
+                model: r.get(0)?,
+                context_tokens: r.get(1)?,
+                session_count: r.get(2)?,
+                first_seen_ms: r.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTimespan {
+    model: String,
+    first_seen_ms: i64,
+    last_seen_ms: i64,
+    active_days: i64,
+    is_current: bool,
+}
+
+#[tauri::command]
+fn get_model_first_last_seen(db_path: Option<String>) -> Result<Vec<ModelTimespan>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT model, MIN(ts_ms) AS first_seen, MAX(ts_ms)
+            FROM samples
+            WHERE model IS NOT NULL
+            GROUP BY model
+            ORDER BY first_seen ASC, model ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            let first_seen_ms: i64 = r.get(1)?;
+            let last_seen_ms: i64 = r.get(2)?;
+            Ok(ModelTimespan {
+                model: r.get(0)?,
+                first_seen_ms,
+                last_seen_ms,
+                active_days: (last_seen_ms - first_seen_ms) / DAY_MS,
+                is_current: now - last_seen_ms <= DAY_MS,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const DEFAULT_HIGH_PERCENT_LIMIT: i64 = 50;
+
+#[tauri::command]
+fn get_samples_with_high_percent_used(
+    threshold_pct: i64,
+    limit: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM samples
+        WHERE percent_used >= ?1
+        ORDER BY percent_used DESC, ts_ms DESC
+        LIMIT ?2
+        "#,
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            [
+                threshold_pct,
+                limit.unwrap_or(DEFAULT_HIGH_PERCENT_LIMIT).max(0),
+            ],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const EXPORT_SIZE_SAMPLE_ROWS: i64 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    row_count: i64,
+    session_count: i64,
+    estimated_size_bytes: i64,
+    date_range_ms: i64,
+    models: Vec<String>,
+}
+
+#[tauri::command]
+fn get_export_manifest(
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+    db_path: Option<String>,
+) -> Result<ExportManifest, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let start = start_ms.unwrap_or(i64::MIN);
+    let end = end_ms.unwrap_or(i64::MAX);
+    let (row_count, session_count, first, last): (i64, i64, Option<i64>, Option<i64>) =
+        timed_query(
+            &conn,
+            r#"
+        SELECT COUNT(*), COUNT(DISTINCT session_key), MIN(ts_ms), MAX(ts_ms)
+        FROM samples
+        WHERE ts_ms >= ?1 AND ts_ms <= ?2
+        "#,
+            [start, end],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT DISTINCT model
+            FROM samples
+            WHERE model IS NOT NULL AND ts_ms >= ?1 AND ts_ms <= ?2
+            ORDER BY model ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let models = stmt
+        .query_map([start, end], |r| r.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // Size a JSONL export by serializing the first rows exactly as an export would (plus newline).
+    let sql = format!(
+        "SELECT {} FROM samples WHERE ts_ms >= ?1 AND ts_ms <= ?2 ORDER BY ts_ms ASC LIMIT ?3",
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let sizes = stmt
+        .query_map([start, end, EXPORT_SIZE_SAMPLE_ROWS], sample_from_row)
+        .map_err(|e| e.to_string())?
+        .map(|row| {
+            let row = row.map_err(|e| e.to_string())?;
+            serde_json::to_string(&row)
+                .map(|line| line.len() as f64 + 1.0)
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+    let avg_row_size = mean(&sizes).unwrap_or(0.0);
+
+    Ok(ExportManifest {
+        row_count,
+        session_count,
+        estimated_size_bytes: (row_count as f64 * avg_row_size).round() as i64,
+        date_range_ms: match (first, last) {
+            (Some(f), Some(l)) => l - f,
+            _ => 0,
+        },
+        models,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterpolatedCount {
+    ts_ms: i64,
+    total_tokens: f64,
+    interpolated: bool,
+}
+
+#[tauri::command]
+fn get_token_count_at_time(
+    session_key: String,
+    ts_ms: i64,
+    db_path: Option<String>,
+) -> Result<Option<InterpolatedCount>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Nearest sample with a token count at or before / at or after `ts_ms`.
+    let bounds: (Option<i64>, Option<i64>, Option<i64>, Option<i64>) = timed_query(
+        &conn,
+        r#"
+        SELECT
+          (SELECT ts_ms FROM samples
+           WHERE session_key = ?1 AND ts_ms <= ?2 AND total_tokens IS NOT NULL
+           ORDER BY ts_ms DESC LIMIT 1),
+          (SELECT total_tokens FROM samples
+           WHERE session_key = ?1 AND ts_ms <= ?2 AND total_tokens IS NOT NULL
+           ORDER BY ts_ms DESC LIMIT 1),
+          (SELECT ts_ms FROM samples
+           WHERE session_key = ?1 AND ts_ms >= ?2 AND total_tokens IS NOT NULL
+           ORDER BY ts_ms ASC LIMIT 1),
+          (SELECT total_tokens FROM samples
+           WHERE session_key = ?1 AND ts_ms >= ?2 AND total_tokens IS NOT NULL
+           ORDER BY ts_ms ASC LIMIT 1)
+        "#,
+        rusqlite::params![session_key, ts_ms],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+    )?;
+
+    Ok(match bounds {
+        (Some(t0), Some(v0), _, _) if t0 == ts_ms => Some(InterpolatedCount {
+            ts_ms,
+            total_tokens: v0 as f64,
+            interpolated: false,
+        }),
+        (Some(t0), Some(v0), Some(t1), Some(v1)) => {
+            let frac = (ts_ms - t0) as f64 / (t1 - t0) as f64;
+            Some(InterpolatedCount {
+                ts_ms,
+                total_tokens: v0 as f64 + (v1 - v0) as f64 * frac,
+                interpolated: true,
+            })
+        }
+        // Outside the session's range: nothing to interpolate between.
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayActivity {
+    date_label: String,
+    active_hours: Vec<u8>,
+    total_active_hours: u8,
+}
+
+#[tauri::command]
+fn get_daily_active_hours(
+    days_back: u32,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<DayActivity>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Same local-day window as get_unique_session_count_by_day.
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let days = days_back.min(MAX_DAYS_BACK) as i64;
+    let today = (now_ms() + offset_ms).div_euclid(DAY_MS);
+    let first_day = today - days + 1;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT DISTINCT (ts_ms + ?1) / ?2 AS hour
+            FROM samples
+            WHERE ts_ms >= ?3
+            ORDER BY hour ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let hours = stmt
+        .query_map([offset_ms, HOUR_MS, first_day * DAY_MS - offset_ms], |r| {
+            r.get::<_, i64>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut by_day: HashMap<i64, Vec<u8>> = HashMap::new();
+    for h in hours {
+        by_day
+            .entry(h.div_euclid(24))
+            .or_default()
+            .push(h.rem_euclid(24) as u8);
+    }
+
+    Ok((first_day..=today)
+        .map(|day| {
+            let active_hours = by_day.remove(&day).unwrap_or_default();
+            DayActivity {
+                date_label: local_date_label(day * DAY_MS - offset_ms, tz_offset_minutes),
+                total_active_hours: active_hours.len() as u8,
+                active_hours,
+            }
+        })
+        .collect())
+}
+
+// App-owned tables keyed by session_key that outlive their samples unless pruned.
+const SESSION_SCOPED_TABLES: [&str; 3] = ["session_tags", "annotations", "budget_adjustment"];
+
+// Deletes rows in SESSION_SCOPED_TABLES whose session has no samples left.
+fn prune_orphans(conn: &Connection) -> Result<i64, String> {
+    let mut deleted = 0i64;
+    for table in SESSION_SCOPED_TABLES {
+        if !table_exists(conn, table)? {
+            continue;
+        }
+        // The IS NOT NULL matters: a NULL in a NOT IN list makes the predicate never true.
+        let sql = format!(
+            r#"
+            DELETE FROM {}
+            WHERE session_key NOT IN (
+                SELECT DISTINCT session_key FROM samples WHERE session_key IS NOT NULL
+            )
+            "#,
+            table
+        );
+        deleted += conn.execute(&sql, []).map_err(|e| e.to_string())? as i64;
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+fn prune_orphaned_tags(db_path: Option<String>) -> Result<i64, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let mut conn = open_db(&db_path)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let deleted = prune_orphans(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(deleted)
+}
+
+const MIN_STABILITY_SAMPLES: usize = 5;
+
+/// `1 - stddev / mean` of the session's per-pair tokens/s, clamped to `[0.0, 1.0]`:
+/// 1.0 is a perfectly steady rate (streaming), 0.0 is very bursty (batch).
+/// `None` with fewer than five samples or when the mean rate is zero.
+#[tauri::command]
+fn get_session_stability_score(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    if samples.len() < MIN_STABILITY_SAMPLES {
+        return Ok(None);
+    }
+    let rates: Vec<f64> = pair_rates(&samples).into_iter().map(|(_, r)| r).collect();
+    Ok(match (mean(&rates), std_dev(&rates)) {
+        (Some(m), Some(sd)) if m > 0.0 => Some((1.0 - sd / m).clamp(0.0, 1.0)),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxContext {
+    model: Option<String>,
+    max_context_tokens: i64,
+    observed_at_ms: i64,
+    session_key: String,
+}
+
+#[tauri::command]
+fn get_max_observed_context_window(db_path: Option<String>) -> Result<Vec<MaxContext>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Earliest sample at the maximum, so observed_at_ms is when that size first showed up.
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH ranked AS (
+                SELECT model, context_tokens, ts_ms, session_key,
+                       ROW_NUMBER() OVER (
+                           PARTITION BY model ORDER BY context_tokens DESC, ts_ms ASC
+                       ) AS rn
+                FROM samples
+                WHERE context_tokens IS NOT NULL AND session_key IS NOT NULL
+            )
+            SELECT model, context_tokens, ts_ms, session_key
+            FROM ranked
+            WHERE rn = 1
+            ORDER BY model ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(MaxContext {
+                model: r.get(0)?,
+                max_context_tokens: r.get(1)?,
+                observed_at_ms: r.get(2)?,
+                session_key: r.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const SATURATION_PCT: i64 = 95;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaturationTiming {
+    session_start_ms: i64,
+    saturation_ts_ms: Option<i64>,
+    time_to_saturation_ms: Option<i64>,
+    peak_percent_used: Option<i64>,
+}
+
+#[tauri::command]
+fn get_time_to_context_saturation(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Option<SaturationTiming>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let Some(first) = samples.first() else {
+        return Ok(None);
+    };
+    let saturation_ts_ms = samples
+        .iter()
+        .find(|s| s.percent_used.is_some_and(|p| p >= SATURATION_PCT))
+        .map(|s| s.ts_ms);
+
+    Ok(Some(SaturationTiming {
+        session_start_ms: first.ts_ms,
+        saturation_ts_ms,
+        time_to_saturation_ms: saturation_ts_ms.map(|t| t - first.ts_ms),
+        peak_percent_used: samples.iter().filter_map(|s| s.percent_used).max(),
+    }))
+}
+
+const ZERO_REMAINING_LIMIT: i64 = 100;
+
+// Samples taken with the context completely full, newest first.
+#[tauri::command]
+fn get_samples_with_zero_remaining_tokens(
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM samples
+        WHERE remaining_tokens IS NOT NULL AND remaining_tokens <= 0
+        ORDER BY ts_ms DESC
+        LIMIT ?1
+        "#,
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([ZERO_REMAINING_LIMIT], sample_from_row)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRatioInfo {
+    session_key: String,
+    model: Option<String>,
+    input_tokens: i64,
+    output_tokens: i64,
+    ratio: f64,
+}
+
+// Sessions whose input/output delta ratio is at least `min_ratio`, most input-heavy first.
+#[tauri::command]
+fn get_high_input_output_ratio_sessions(
+    min_ratio: f64,
+    db_path: Option<String>,
+) -> Result<Vec<SessionRatioInfo>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out: Vec<SessionRatioInfo> = load_session_totals(&conn)?
+        .into_iter()
+        .filter_map(|t| {
+            let (Some(input_tokens), Some(output_tokens)) = (t.input_tokens, t.output_tokens)
+            else {
+                return None;
+            };
+            if output_tokens <= 0 {
+                return None;
+            }
+            let ratio = input_tokens as f64 / output_tokens as f64;
+            (ratio >= min_ratio).then_some(SessionRatioInfo {
+                session_key: t.session_key,
+                model: t.model,
+                input_tokens,
+                output_tokens,
+                ratio,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioAnomaly {
+    session_key: String,
+    ts_ms: i64,
+    rx_bytes_per_token: f64,
+    total_tokens_delta: i64,
+    net_rx_bytes_delta: i64,
+}
+
+// Adjacent sample pairs that pulled in more than `threshold_bytes_per_token` rx bytes
+// per token (base64 audio, images, ...). Pairs that produced no tokens are skipped.
+#[tauri::command]
+fn get_network_to_token_ratio_anomalies(
+    threshold_bytes_per_token: f64,
+    db_path: Option<String>,
+) -> Result<Vec<RatioAnomaly>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        for w in session.windows(2) {
+            let (a, b) = (&w[0], &w[1]);
+            let (Some(net_rx_bytes_delta), Some(total_tokens_delta)) = (
+                counter_delta(a.net_rx_bytes, b.net_rx_bytes),
+                counter_delta(a.total_tokens, b.total_tokens),
+            ) else {
+                continue;
+            };
+            if total_tokens_delta <= 0 {
+                continue;
+            }
+            let rx_bytes_per_token = net_rx_bytes_delta as f64 / total_tokens_delta as f64;
+            if rx_bytes_per_token > threshold_bytes_per_token {
+                out.push(RatioAnomaly {
+                    session_key: b.session_key.clone().unwrap_or_default(),
+                    ts_ms: b.ts_ms,
+                    rx_bytes_per_token,
+                    total_tokens_delta,
+                    net_rx_bytes_delta,
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| b.rx_bytes_per_token.total_cmp(&a.rx_bytes_per_token));
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostTimePoint {
+    ts_ms: i64,
+    incremental_input_tokens: Option<i64>,
+    incremental_output_tokens: Option<i64>,
+    incremental_cost_usd: Option<f64>,
+    cumulative_cost_usd: f64,
+}
+
+// Cost of each adjacent-pair delta in a session, priced by the later sample's model.
+// Pairs across a counter reset (or without a price) add nothing to the running total.
+#[tauri::command]
+fn get_session_cost_over_time(
+    session_key: String,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<CostTimePoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    Ok(cost_timeline(&samples, &cost_config))
+}
+
+fn cost_timeline(samples: &[SampleRow], cost_config: &CostConfig) -> Vec<CostTimePoint> {
+    let mut cumulative_cost_usd = 0.0;
+    samples
+        .windows(2)
+        .map(|w| {
+            let (a, b) = (&w[0], &w[1]);
+            let incremental_input_tokens = counter_delta(a.input_tokens, b.input_tokens);
+            let incremental_output_tokens = counter_delta(a.output_tokens, b.output_tokens);
+            let incremental_cost_usd = match (incremental_input_tokens, incremental_output_tokens) {
+                (None, None) => None,
+                (i, o) => cost_config.cost_usd(b.model.as_deref(), i.unwrap_or(0), o.unwrap_or(0)),
+            };
+            cumulative_cost_usd += incremental_cost_usd.unwrap_or(0.0);
+            CostTimePoint {
+                ts_ms: b.ts_ms,
+                incremental_input_tokens,
+                incremental_output_tokens,
+                incremental_cost_usd,
+                cumulative_cost_usd,
+            }
+        })
+        .collect()
+}
+
+const DEFAULT_PERCENT_RANGE_LIMIT: i64 = 100;
+
+#[tauri::command]
+fn get_samples_by_percent_used_range(
+    min_pct: i64,
+    max_pct: i64,
+    limit: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    if min_pct > max_pct {
+        return Err(format!(
+            "min_pct {} is greater than max_pct {}",
+            min_pct, max_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM samples
+        WHERE percent_used >= ?1 AND percent_used <= ?2
+        ORDER BY ts_ms DESC
+        LIMIT ?3
+        "#,
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            [
+                min_pct,
+                max_pct,
+                limit.unwrap_or(DEFAULT_PERCENT_RANGE_LIMIT).max(0),
+            ],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtilizationBucket {
+    // pct_low inclusive, pct_high exclusive
+    pct_low: i64,
+    pct_high: i64,
+    mean_tokens_per_s: f64,
+    sample_count: i64,
+}
+
+// Mean pair rate grouped by the earlier sample's percent_used; only non-empty buckets.
+#[tauri::command]
+fn get_throughput_by_context_utilization(
+    bucket_size_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<UtilizationBucket>, String> {
+    if !(1..=100).contains(&bucket_size_pct) {
+        return Err(format!(
+            "bucket_size_pct must be between 1 and 100, got {}",
+            bucket_size_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut by_bucket: std::collections::BTreeMap<i64, Vec<f64>> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        for w in session.windows(2) {
+            if let (Some(pct), Some(rate)) = (w[0].percent_used, pair_rate(&w[0], &w[1])) {
+                by_bucket
+                    .entry(pct.div_euclid(bucket_size_pct))
+                    .or_default()
+                    .push(rate);
+            }
+        }
+    }
+
+    Ok(by_bucket
+        .into_iter()
+        .map(|(bucket, rates)| UtilizationBucket {
+            pct_low: bucket * bucket_size_pct,
+            pct_high: (bucket + 1) * bucket_size_pct,
+            mean_tokens_per_s: mean(&rates).unwrap_or(0.0),
+            sample_count: rates.len() as i64,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStats {
+    session_key: String,
+    // model of the most recent sample
+    model: Option<String>,
+    duration_ms: i64,
+    sample_count: i64,
+    total_tokens_delta: Option<i64>,
+    mean_tokens_per_s: Option<f64>,
+    p50_tokens_per_s: Option<f64>,
+    p95_tokens_per_s: Option<f64>,
+    max_tokens_per_s: Option<f64>,
+    peak_percent_used: Option<i64>,
+    // None without a cost config or a price for the model
+    estimated_cost_usd: Option<f64>,
+    model_switches: i64,
+    // gaps between samples longer than PULSE_IDLE_AFTER_MS
+    idle_periods_count: i64,
+}
+
+#[tauri::command]
+fn get_session_summary_stats(
+    session_key: String,
+    cost_config: Option<CostConfig>,
+    db_path: Option<String>,
+) -> Result<SessionStats, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    session_stats(session_key, &samples, cost_config.as_ref())
+}
+
+fn session_stats(
+    session_key: String,
+    samples: &[SampleRow],
+    cost_config: Option<&CostConfig>,
+) -> Result<SessionStats, String> {
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => (f, l),
+        _ => return Err(format!("no samples for session {}", session_key)),
+    };
+
+    let mut rates: Vec<f64> = pair_rates(samples).into_iter().map(|(_, r)| r).collect();
+    rates.sort_by(|a, b| a.total_cmp(b));
+    let estimated_cost_usd = cost_config.and_then(|c| {
+        c.cost_usd(
+            last.model.as_deref(),
+            counter_delta(first.input_tokens, last.input_tokens).unwrap_or(0),
+            counter_delta(first.output_tokens, last.output_tokens).unwrap_or(0),
+        )
+    });
+    // Same rule as `get_model_switch_count`: a NULL model on either side is not a switch.
+    let model_switches = samples
+        .windows(2)
+        .filter(|w| match (&w[0].model, &w[1].model) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        })
+        .count() as i64;
+    let idle_periods_count = samples
+        .windows(2)
+        .filter(|w| w[1].ts_ms - w[0].ts_ms > PULSE_IDLE_AFTER_MS)
+        .count() as i64;
+
+    Ok(SessionStats {
+        model: last.model.clone(),
+        duration_ms: last.ts_ms - first.ts_ms,
+        sample_count: samples.len() as i64,
+        total_tokens_delta: counter_delta(first.total_tokens, last.total_tokens),
+        mean_tokens_per_s: mean(&rates),
+        p50_tokens_per_s: percentile(&rates, 50.0),
+        p95_tokens_per_s: percentile(&rates, 95.0),
+        max_tokens_per_s: rates.last().copied(),
+        peak_percent_used: samples.iter().filter_map(|s| s.percent_used).max(),
+        estimated_cost_usd,
+        model_switches,
+        idle_periods_count,
+        session_key,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelContextUsage {
+    model: String,
+    avg_context_tokens: f64,
+    min_context_tokens: i64,
+    max_context_tokens: i64,
+    avg_percent_used: f64,
+}
+
+#[tauri::command]
+fn get_average_context_tokens_per_model(
+    db_path: Option<String>,
+) -> Result<Vec<ModelContextUsage>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT model, AVG(context_tokens), MIN(context_tokens), MAX(context_tokens),
+                   AVG(percent_used)
+            FROM samples
+            WHERE model IS NOT NULL AND context_tokens IS NOT NULL AND percent_used IS NOT NULL
+            GROUP BY model
+            ORDER BY model ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(ModelContextUsage {
+                model: r.get(0)?,
+                avg_context_tokens: r.get(1)?,
+                min_context_tokens: r.get(2)?,
+                max_context_tokens: r.get(3)?,
+                avg_percent_used: r.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingSampleReport {
+    // sessions seen before start_ms that still have samples in the range
+    sessions_with_samples: i64,
+    sessions_without_samples: Vec<String>,
+}
+
+// Sessions that had samples before `start_ms` but none in `[start_ms, end_ms]`.
+// With fewer than `min_sessions_expected` earlier sessions there is too little history
+// to call a silence suspicious, so nothing is reported as missing.
+#[tauri::command]
+fn get_sessions_without_samples_in_range(
+    start_ms: i64,
+    end_ms: i64,
+    min_sessions_expected: i64,
+    db_path: Option<String>,
+) -> Result<MissingSampleReport, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT p.session_key,
+                   EXISTS(
+                     SELECT 1 FROM samples s
+                     WHERE s.session_key = p.session_key AND s.ts_ms >= ?1 AND s.ts_ms <= ?2
+                   )
+            FROM (
+                SELECT DISTINCT session_key
+                FROM samples
+                WHERE session_key IS NOT NULL AND ts_ms < ?1
+            ) p
+            ORDER BY p.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([start_ms, end_ms], |r| {
+            Ok((r.get::<_, String>(0)?, r.get::<_, bool>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut sessions_with_samples = 0;
+    let mut sessions_without_samples = Vec::new();
+    let mut prior_sessions = 0;
+    for row in rows {
+        let (session_key, has_samples) = row.map_err(|e| e.to_string())?;
+        prior_sessions += 1;
+        if has_samples {
+            sessions_with_samples += 1;
+        } else {
+            sessions_without_samples.push(session_key);
+        }
+    }
+    if prior_sessions < min_sessions_expected {
+        sessions_without_samples.clear();
+    }
+
+    Ok(MissingSampleReport {
+        sessions_with_samples,
+        sessions_without_samples,
+    })
+}
+
+const GROWTH_TARGET_BYTES: f64 = 1e9;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthRate {
+    current_size_bytes: i64,
+    // over the last 7 days
+    sample_rate_per_hour: f64,
+    // current size spread over the age of the oldest sample
+    estimated_size_bytes_per_day: i64,
+    // None once the file is >= 1GB or when there is no growth to extrapolate
+    estimated_days_until_1gb: Option<f64>,
+}
+
+#[tauri::command]
+fn get_database_growth_rate(db_path: Option<String>) -> Result<GrowthRate, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let current_size_bytes = std::fs::metadata(&db_path)
+        .map_err(|e| e.to_string())?
+        .len() as i64;
+    let now = now_ms();
+    let (oldest_ts_ms, recent_count): (Option<i64>, i64) = timed_query(
+        &conn,
+        "SELECT MIN(ts_ms), SUM(CASE WHEN ts_ms >= ?1 THEN 1 ELSE 0 END) FROM samples",
+        [now - 7 * DAY_MS],
+        |r| Ok((r.get(0)?, r.get::<_, Option<i64>>(1)?.unwrap_or(0))),
+    )?;
+
+    let age_days = oldest_ts_ms.map_or(0.0, |ts| (now - ts) as f64 / DAY_MS as f64);
+    let bytes_per_day = if age_days > 0.0 {
+        current_size_bytes as f64 / age_days
+    } else {
+        0.0
+    };
+    let estimated_days_until_1gb = ((current_size_bytes as f64) < GROWTH_TARGET_BYTES
+        && bytes_per_day > 0.0)
+        .then(|| (GROWTH_TARGET_BYTES - current_size_bytes as f64) / bytes_per_day);
+
+    Ok(GrowthRate {
+        current_size_bytes,
+        sample_rate_per_hour: recent_count as f64 / (7.0 * 24.0),
+        estimated_size_bytes_per_day: bytes_per_day.round() as i64,
+        estimated_days_until_1gb,
+    })
+}
+
+const MODEL_WINDOW_GAP_MS: i64 = HOUR_MS;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelWindow {
+    model: String,
+    start_ms: i64,
+    end_ms: i64,
+    duration_ms: i64,
+}
+
+// Continuous stretches in which each model shows up in samples; a gap longer than an
+// hour starts a new window. Ordered by model, then start time.
+#[tauri::command]
+fn get_model_availability_windows(db_path: Option<String>) -> Result<Vec<ModelWindow>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT DISTINCT model, ts_ms
+            FROM samples
+            WHERE model IS NOT NULL
+            ORDER BY model ASC, ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut out: Vec<ModelWindow> = Vec::new();
+    for row in rows {
+        let (model, ts_ms) = row.map_err(|e| e.to_string())?;
+        match out.last_mut() {
+            Some(w) if w.model == model && ts_ms - w.end_ms <= MODEL_WINDOW_GAP_MS => {
+                w.end_ms = ts_ms;
+                w.duration_ms = ts_ms - w.start_ms;
+            }
+            _ => out.push(ModelWindow {
+                model,
+                start_ms: ts_ms,
+                end_ms: ts_ms,
+                duration_ms: 0,
+            }),
+        }
+    }
+    Ok(out)
+}
+
+#[tauri::command]
+fn get_realtime_cost_rate(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    Ok(realtime_cost_per_hour(&live_metrics(&conn)?, &cost_config))
+}
+
+// Sessions costing at least the `percentile`-th percentile of all priced sessions,
+// most expensive first. Sessions without a configured price are left out.
+#[tauri::command]
+fn get_percentile_cost_sessions(
+    percentile: f64,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<SessionCostBreakdown>, String> {
+    if !(percentile > 0.0 && percentile <= 100.0) {
+        return Err(format!(
+            "percentile must be in (0, 100], got {}",
+            percentile
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut sessions: Vec<SessionCostBreakdown> = load_session_totals(&conn)?
+        .into_iter()
+        .filter_map(|t| {
+            let price = cost_config.price_for(t.model.as_deref())?;
+            Some(cost_breakdown(
+                t.session_key,
+                price,
+                t.input_tokens.unwrap_or(0),
+                t.output_tokens.unwrap_or(0),
+            ))
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.total_cost_usd.total_cmp(&b.total_cost_usd));
+    let costs: Vec<f64> = sessions.iter().map(|s| s.total_cost_usd).collect();
+    let Some(threshold) = self::percentile(&costs, percentile) else {
+        return Ok(Vec::new());
+    };
+
+    sessions.retain(|s| s.total_cost_usd >= threshold);
+    sessions.reverse();
+    Ok(sessions)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnexpectedNullReport {
+    count: i64,
+    affected_sessions: Vec<String>,
+    first_occurrence_ms: Option<i64>,
+    last_occurrence_ms: Option<i64>,
+}
+
+// Samples that carry a session_key but no model, which the collector should never write.
+#[tauri::command]
+fn get_samples_with_unexpected_model_null(
+    db_path: Option<String>,
+) -> Result<UnexpectedNullReport, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let (count, first_occurrence_ms, last_occurrence_ms) = timed_query(
+        &conn,
+        r#"
+        SELECT COUNT(*), MIN(ts_ms), MAX(ts_ms)
+        FROM samples
+        WHERE session_key IS NOT NULL AND model IS NULL
+        "#,
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT DISTINCT session_key
+            FROM samples
+            WHERE session_key IS NOT NULL AND model IS NULL
+            ORDER BY session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let affected_sessions = stmt
+        .query_map([], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(UnexpectedNullReport {
+        count,
+        affected_sessions,
+        first_occurrence_ms,
+        last_occurrence_ms,
+    })
+}
+
+const PROFILE_HISTOGRAM_BUCKETS: u32 = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextPoint {
+    ts_ms: i64,
+    percent_used: i64,
+    context_tokens: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSwitch {
+    ts_ms: i64,
+    from_model: String,
+    to_model: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPeriod {
+    start_ms: i64,
+    end_ms: i64,
+    duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionProfile {
+    stats: Option<SessionStats>,
+    // tokens/s of adjacent pairs; None with fewer than two usable samples
+    rate_histogram: Option<Vec<HistogramBucket>>,
+    context_history: Option<Vec<ContextPoint>>,
+    model_switches: Option<Vec<ModelSwitch>>,
+    // gaps longer than PULSE_IDLE_AFTER_MS
+    idle_periods: Option<Vec<SessionPeriod>>,
+    // runs of pairs faster than twice the session's mean rate (the pulse's burst rule)
+    burst_periods: Option<Vec<SessionPeriod>>,
+    // crossings of SATURATION_PCT
+    saturation_events: Option<Vec<SaturationEvent>>,
+    // None without a cost config
+    cost_timeline: Option<Vec<CostTimePoint>>,
+}
+
+// Everything the session detail drawer shows, from one load of the session's samples.
+#[tauri::command]
+fn get_complete_session_profile(
+    session_key: String,
+    cost_config: Option<CostConfig>,
+    db_path: Option<String>,
+) -> Result<SessionProfile, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    if samples.is_empty() {
+        return Err(format!("no samples for session {}", session_key));
+    }
+
+    let rates: Vec<f64> = pair_rates(&samples).into_iter().map(|(_, r)| r).collect();
+    let context_history = samples
+        .iter()
+        .filter_map(|s| {
+            s.percent_used.map(|percent_used| ContextPoint {
+                ts_ms: s.ts_ms,
+                percent_used,
+                context_tokens: s.context_tokens,
+            })
+        })
+        .collect();
+    let model_switches = samples
+        .windows(2)
+        .filter_map(|w| match (&w[0].model, &w[1].model) {
+            (Some(a), Some(b)) if a != b => Some(ModelSwitch {
+                ts_ms: w[1].ts_ms,
+                from_model: a.clone(),
+                to_model: b.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+    let idle_periods = samples
+        .windows(2)
+        .filter(|w| w[1].ts_ms - w[0].ts_ms > PULSE_IDLE_AFTER_MS)
+        .map(|w| SessionPeriod {
+            start_ms: w[0].ts_ms,
+            end_ms: w[1].ts_ms,
+            duration_ms: w[1].ts_ms - w[0].ts_ms,
+        })
+        .collect();
+
+    let mut burst_periods: Vec<SessionPeriod> = Vec::new();
+    if let Some(m) = mean(&rates).filter(|m| *m > 0.0) {
+        let mut prev_bursting = false;
+        for w in samples.windows(2) {
+            let bursting = pair_rate(&w[0], &w[1]).is_some_and(|r| r > 2.0 * m);
+            match burst_periods.last_mut() {
+                Some(p) if bursting && prev_bursting => {
+                    p.end_ms = w[1].ts_ms;
+                    p.duration_ms = p.end_ms - p.start_ms;
+                }
+                _ if bursting => burst_periods.push(SessionPeriod {
+                    start_ms: w[0].ts_ms,
+                    end_ms: w[1].ts_ms,
+                    duration_ms: w[1].ts_ms - w[0].ts_ms,
+                }),
+                _ => {}
+            }
+            prev_bursting = bursting;
+        }
+    }
+
+    Ok(SessionProfile {
+        stats: session_stats(session_key, &samples, cost_config.as_ref()).ok(),
+        rate_histogram: (!rates.is_empty())
+            .then(|| histogram_buckets(&rates, PROFILE_HISTOGRAM_BUCKETS)),
+        context_history: Some(context_history),
+        model_switches: Some(model_switches),
+        idle_periods: Some(idle_periods),
+        burst_periods: Some(burst_periods),
+        saturation_events: Some(saturation_crossings(&samples, SATURATION_PCT)),
+        cost_timeline: cost_config.as_ref().map(|c| cost_timeline(&samples, c)),
+    })
+}
+
+const TOKEN_DEBT_PCT: i64 = 80;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDebt {
+    peak_percent_used: i64,
+    // context_tokens - remaining_tokens lost across compactions; None without both columns
+    tokens_wasted_at_limit: Option<i64>,
+    // cost of the deltas recorded while above TOKEN_DEBT_PCT; None without a price
+    cost_of_near_limit_operation_usd: Option<f64>,
+}
+
+// None if the session never went above TOKEN_DEBT_PCT of its context.
+#[tauri::command]
+fn get_token_debt(
+    session_key: String,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Option<TokenDebt>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let Some(peak_percent_used) = samples
+        .iter()
+        .filter_map(|s| s.percent_used)
+        .max()
+        .filter(|p| *p > TOKEN_DEBT_PCT)
+    else {
+        return Ok(None);
+    };
+
+    let used = |s: &SampleRow| match (s.context_tokens, s.remaining_tokens) {
+        (Some(ctx), Some(rem)) => Some(ctx - rem),
+        _ => None,
+    };
+    let mut tokens_wasted_at_limit = None;
+    let mut cost_of_near_limit_operation_usd = None;
+    for w in samples.windows(2) {
+        let (a, b) = (&w[0], &w[1]);
+        if let (Some(before), Some(after)) = (used(a), used(b)) {
+            // A drop in used context is a compaction; the difference fell off the window.
+            let dropped = (before - after).max(0);
+            tokens_wasted_at_limit = Some(tokens_wasted_at_limit.unwrap_or(0) + dropped);
+        }
+        if a.percent_used.is_some_and(|p| p > TOKEN_DEBT_PCT) {
+            let cost = cost_config.cost_usd(
+                b.model.as_deref(),
+                counter_delta(a.input_tokens, b.input_tokens).unwrap_or(0),
+                counter_delta(a.output_tokens, b.output_tokens).unwrap_or(0),
+            );
+            if let Some(c) = cost {
+                cost_of_near_limit_operation_usd =
+                    Some(cost_of_near_limit_operation_usd.unwrap_or(0.0) + c);
+            }
+        }
+    }
+
+    Ok(Some(TokenDebt {
+        peak_percent_used,
+        tokens_wasted_at_limit,
+        cost_of_near_limit_operation_usd,
+    }))
+}
+
+// No samples from any session for this long means the collector was not running.
+const COLLECTOR_RESTART_GAP_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectorEvent {
+    ts_ms: i64,
+    event_type: String,
+    payload: Option<String>,
+}
+
+#[tauri::command]
+fn record_collector_event(
+    event_type: String,
+    payload: Option<String>,
+    db_path: Option<String>,
+) -> Result<(), String> {
+    if event_type.trim().is_empty() {
+        return Err("event_type must not be empty".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+
+    conn.execute(
+        "INSERT INTO collector_events (ts_ms, event_type, payload) VALUES (?1, ?2, ?3)",
+        rusqlite::params![now_ms(), event_type, payload],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Sample gaps longer than COLLECTOR_RESTART_GAP_MS (`?1`) without a `restart_detected` event yet,
+// as the first timestamp after the gap and the last one before it.
+const UNRECORDED_RESTARTS_SQL: &str = r#"
+    SELECT ts_ms, prev_ts
+    FROM (
+        SELECT ts_ms, LAG(ts_ms) OVER (ORDER BY ts_ms) AS prev_ts
+        FROM (SELECT DISTINCT ts_ms FROM samples)
+    ) g
+    WHERE prev_ts IS NOT NULL AND ts_ms - prev_ts > ?1
+      AND NOT EXISTS (
+        SELECT 1 FROM collector_events e
+        WHERE e.event_type = 'restart_detected' AND e.ts_ms = g.ts_ms
+      )
+"#;
+
+// Lists all events, first recording a `restart_detected` event (at the resume timestamp)
+// for every sample gap longer than COLLECTOR_RESTART_GAP_MS that isn't recorded yet. The gaps are
+// looked for read-only; a writable connection is opened only when there are some to record.
+#[tauri::command]
+fn get_collector_events(db_path: Option<String>) -> Result<Vec<CollectorEvent>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Without the table the DB hasn't been migrated yet, so `open_db` has to run anyway.
+    let needs_write = !table_exists(&conn, "collector_events")?
+        || timed_query(
+            &conn,
+            &format!("SELECT EXISTS({})", UNRECORDED_RESTARTS_SQL),
+            [COLLECTOR_RESTART_GAP_MS],
+            |r| r.get::<_, bool>(0),
+        )?;
+    if needs_write {
+        open_db(&db_path)?
+            .execute(
+                &format!(
+                    r#"
+                    INSERT INTO collector_events (ts_ms, event_type, payload)
+                    SELECT ts_ms, 'restart_detected',
+                           json_object('gapStartMs', prev_ts, 'gapMs', ts_ms - prev_ts)
+                    FROM ({})
+                    "#,
+                    UNRECORDED_RESTARTS_SQL
+                ),
+                [COLLECTOR_RESTART_GAP_MS],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT ts_ms, event_type, payload
+            FROM collector_events
+            ORDER BY ts_ms ASC, rowid ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| {
+            Ok(CollectorEvent {
+                ts_ms: r.get(0)?,
+                event_type: r.get(1)?,
+                payload: r.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCostComparison {
+    model: String,
+    cost_usd: f64,
+    observed_session_count: i64,
+}
+
+// What `token_count` tokens (half input, half output) cost under each model's pricing,
+// cheapest first; models their config has no price for are skipped. Sessions are counted
+// by substring match on the model, like `CostConfig::price_for`.
+#[tauri::command]
+fn get_model_token_cost_comparison(
+    token_count: i64,
+    cost_config: HashMap<String, CostConfig>,
+    db_path: Option<String>,
+) -> Result<Vec<ModelCostComparison>, String> {
+    if token_count < 0 {
+        return Err(format!("token_count must be >= 0, got {}", token_count));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let input_tokens = token_count / 2;
+    let output_tokens = token_count - input_tokens;
+    let mut out = Vec::new();
+    for (model, config) in &cost_config {
+        let Some(cost_usd) = config.cost_usd(Some(model), input_tokens, output_tokens) else {
+            continue;
+        };
+        let observed_session_count: i64 = timed_query(
+            &conn,
+            r#"
+            SELECT COUNT(DISTINCT session_key)
+            FROM samples
+            WHERE model IS NOT NULL AND instr(model, ?1) > 0
+            "#,
+            [model],
+            |r| r.get(0),
+        )?;
+        out.push(ModelCostComparison {
+            model: model.clone(),
+            cost_usd,
+            observed_session_count,
+        });
+    }
+    out.sort_by(|a, b| {
+        a.cost_usd
+            .total_cmp(&b.cost_usd)
+            .then(a.model.cmp(&b.model))
+    });
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHeadroom {
+    session_key: String,
+    model: Option<String>,
+    context_tokens: Option<i64>,
+    remaining_tokens: Option<i64>,
+    headroom_pct: Option<f64>,
+    last_ts_ms: i64,
+}
+
+// Latest sample of every session active in the last PULSE_WINDOW_MS, lowest headroom
+// first; sessions without context/remaining numbers go last.
+#[tauri::command]
+fn get_context_window_headroom(db_path: Option<String>) -> Result<Vec<SessionHeadroom>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT s.session_key, s.model, s.context_tokens, s.remaining_tokens, s.ts_ms
+            FROM samples s
+            JOIN (
+                SELECT session_key, MAX(ts_ms) AS ts_ms
+                FROM samples
+                WHERE session_key IS NOT NULL
+                GROUP BY session_key
+            ) latest ON latest.session_key = s.session_key AND latest.ts_ms = s.ts_ms
+            WHERE s.ts_ms >= ?1
+            ORDER BY s.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([now_ms() - PULSE_WINDOW_MS], |r| {
+            let context_tokens: Option<i64> = r.get(2)?;
+            let remaining_tokens: Option<i64> = r.get(3)?;
+            Ok(SessionHeadroom {
+                session_key: r.get(0)?,
+                model: r.get(1)?,
+                context_tokens,
+                remaining_tokens,
+                headroom_pct: match (remaining_tokens, context_tokens) {
+                    (Some(rem), Some(ctx)) if ctx > 0 => Some(rem as f64 * 100.0 / ctx as f64),
+                    _ => None,
+                },
+                last_ts_ms: r.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    let mut out = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    out.sort_by(|a, b| match (a.headroom_pct, b.headroom_pct) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EfficiencyTrendPoint {
+    session_key: String,
+    session_start_ms: i64,
+    // None when the session recorded no input tokens
+    output_to_input_ratio: Option<f64>,
+    // 1-based, oldest first
+    sessions_rank: u32,
+}
+
+// Output/input ratio of the `session_count` most recently started sessions, oldest first.
+#[tauri::command]
+fn get_efficiency_trend(
+    session_count: u32,
+    db_path: Option<String>,
+) -> Result<Vec<EfficiencyTrendPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // load_session_totals is already ordered by start time.
+    let totals = load_session_totals(&conn)?;
+    let skip = totals.len().saturating_sub(session_count as usize);
+    Ok(totals
+        .into_iter()
+        .skip(skip)
+        .enumerate()
+        .map(|(i, t)| EfficiencyTrendPoint {
+            output_to_input_ratio: match (t.input_tokens, t.output_tokens) {
+                (Some(inp), Some(out)) if inp > 0 => Some(out as f64 / inp as f64),
+                _ => None,
+            },
+            session_key: t.session_key,
+            session_start_ms: t.first_ts_ms,
+            sessions_rank: i as u32 + 1,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBudgetForecast {
+    model: String,
+    tokens_used_today: i64,
+    pct_of_daily_quota: f64,
+    projected_daily_tokens: i64,
+    projected_daily_cost_usd: f64,
+}
+
+// Today's (UTC) usage per model, pro-rated to a full day by the elapsed fraction of it.
+// A session's delta is attributed to its latest model, as in `daily_usage`.
+#[tauri::command]
+fn get_token_budget_forecast_by_model(
+    daily_quota_tokens: i64,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<ModelBudgetForecast>, String> {
+    if daily_quota_tokens <= 0 {
+        return Err("daily_quota_tokens must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let now = now_ms();
+    let day_start = local_day_start_ms(now, 0);
+    let mut by_model: std::collections::BTreeMap<String, (i64, f64)> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session_in_range(&conn, None, day_start, now)? {
+        let (Some(f), Some(l)) = (session.first(), session.last()) else {
+            continue;
+        };
+        let Some(model) = l.model.clone() else {
+            continue;
+        };
+        let input = counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0);
+        let output = counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0);
+        let entry = by_model.entry(model).or_default();
+        entry.0 += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+        entry.1 += cost_config
+            .cost_usd(l.model.as_deref(), input, output)
+            .unwrap_or(0.0);
+    }
+
+    // At least a minute in, so a projection right after midnight doesn't explode.
+    let elapsed_fraction = ((now - day_start).max(60_000)) as f64 / DAY_MS as f64;
+    Ok(by_model
+        .into_iter()
+        .map(|(model, (tokens, cost))| ModelBudgetForecast {
+            model,
+            tokens_used_today: tokens,
+            pct_of_daily_quota: tokens as f64 / daily_quota_tokens as f64 * 100.0,
+            projected_daily_tokens: (tokens as f64 / elapsed_fraction).round() as i64,
+            projected_daily_cost_usd: cost / elapsed_fraction,
+        })
+        .collect())
+}
+
+const MAX_SENSITIVITY_POINTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityPoint {
+    input_ratio: f64,
+    input_tokens: i64,
+    output_tokens: i64,
+    cost_usd: f64,
+}
+
+// Cost of `total_tokens` at each input share, priced for the most recently sampled model
+// (or `default_price` when there are no samples). Only the first 20 ratios are used.
+#[tauri::command]
+fn get_cost_sensitivity_analysis(
+    total_tokens: i64,
+    input_ratios: Vec<f64>,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<SensitivityPoint>, String> {
+    if total_tokens < 0 {
+        return Err(format!("total_tokens must be >= 0, got {}", total_tokens));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let model: Option<String> = timed_query(
+        &conn,
+        "SELECT (SELECT model FROM samples WHERE model IS NOT NULL ORDER BY ts_ms DESC LIMIT 1)",
+        [],
+        |r| r.get(0),
+    )?;
+    let model = model.as_deref();
+    if cost_config.price_for(model).is_none() {
+        return Err(format!(
+            "no price configured for model {}",
+            model.unwrap_or("(unknown)")
+        ));
+    }
+
+    Ok(input_ratios
+        .into_iter()
+        .take(MAX_SENSITIVITY_POINTS)
+        .map(|ratio| {
+            let input_ratio = ratio.clamp(0.0, 1.0);
+            let input_tokens = (total_tokens as f64 * input_ratio).round() as i64;
+            let output_tokens = total_tokens - input_tokens;
+            SensitivityPoint {
+                input_ratio,
+                input_tokens,
+                output_tokens,
+                cost_usd: cost_config
+                    .cost_usd(model, input_tokens, output_tokens)
+                    .unwrap_or(0.0),
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourlyRatePercentile {
+    hour_of_day: u8,
+    rate_at_percentile: Option<f64>,
+    sample_count: i64,
+}
+
+// Pair rates bucketed by the local hour of the later sample; always 24 rows.
+#[tauri::command]
+fn get_token_rate_percentile_by_hour(
+    percentile: f64,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<HourlyRatePercentile>, String> {
+    if !(0.0..=100.0).contains(&percentile) {
+        return Err(format!(
+            "percentile must be in [0, 100], got {}",
+            percentile
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let mut by_hour: Vec<Vec<f64>> = vec![Vec::new(); 24];
+    for session in load_samples_by_session(&conn)? {
+        for (ts_ms, rate) in pair_rates(&session) {
+            let hour = (ts_ms + offset_ms).rem_euclid(DAY_MS) / HOUR_MS;
+            by_hour[hour as usize].push(rate);
+        }
+    }
+
+    Ok(by_hour
+        .into_iter()
+        .enumerate()
+        .map(|(hour, mut rates)| {
+            rates.sort_by(|a, b| a.total_cmp(b));
+            HourlyRatePercentile {
+                hour_of_day: hour as u8,
+                rate_at_percentile: self::percentile(&rates, percentile),
+                sample_count: rates.len() as i64,
+            }
+        })
+        .collect())
+}
+
+const DEFAULT_REACTIVATION_IDLE_MS: i64 = 300_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReactivation {
+    session_key: String,
+    reactivation_count: i64,
+    max_idle_before_reactivation_ms: i64,
+}
+
+// Sessions with at least one gap over the idle threshold followed by more samples,
+// most reactivations first.
+#[tauri::command]
+fn get_session_reactivation_count(
+    idle_threshold_ms: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<SessionReactivation>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH gaps AS (
+                SELECT session_key,
+                       ts_ms - LAG(ts_ms) OVER (PARTITION BY session_key ORDER BY ts_ms) AS gap_ms
+                FROM samples
+                WHERE session_key IS NOT NULL
+            )
+            SELECT session_key, COUNT(*), MAX(gap_ms)
+            FROM gaps
+            WHERE gap_ms > ?1
+            GROUP BY session_key
+            ORDER BY COUNT(*) DESC, session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            [idle_threshold_ms.unwrap_or(DEFAULT_REACTIVATION_IDLE_MS)],
+            |r| {
+                Ok(SessionReactivation {
+                    session_key: r.get(0)?,
+                    reactivation_count: r.get(1)?,
+                    max_idle_before_reactivation_ms: r.get(2)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const MAX_SPARKLINE_POINTS: u32 = 300;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollupWindowSpec {
+    label: String,
+    // the window ends now
+    duration_ms: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardConfig {
+    active_session_threshold_ms: i64,
+    sparkline_points: u32,
+    include_cost: bool,
+    #[serde(default)]
+    cost_config: Option<CostConfig>,
+    #[serde(default)]
+    rollup_windows: Vec<RollupWindowSpec>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    session_key: String,
+    model: Option<String>,
+    last_ts_ms: i64,
+    total_tokens: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardPack {
+    live: Option<LiveMetrics>,
+    // tokens/s of the live session's most recent sample pairs
+    sparkline: Vec<RatePoint>,
+    rollups: Vec<Rollup>,
+    active_sessions: Vec<ActiveSession>,
+    // alert_history entries from the last 24h
+    alerts: Vec<AlertRecord>,
+    health: HealthStatus,
+    // UTC day; only with include_cost and a cost config
+    cost_today_usd: Option<f64>,
+}
+
+// Every dashboard widget from one read transaction, so they all see the same snapshot.
+#[tauri::command]
+fn get_realtime_dashboard_pack(
+    config: DashboardConfig,
+    db_path: Option<String>,
+) -> Result<DashboardPack, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let mut conn = open_readonly(&db_path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let now = now_ms();
+    let cost_config = config.cost_config.as_ref().filter(|_| config.include_cost);
+    // An empty database has no live sample; the rest still renders.
+    let live = live_metrics(&tx).ok().map(|mut m| {
+        m.realtime_cost_per_hour_usd = cost_config.and_then(|c| realtime_cost_per_hour(&m, c));
+        m
+    });
+
+    let sparkline = match live.as_ref().and_then(|m| m.session_key.as_deref()) {
+        Some(session_key) => {
+            let rates = pair_rates(&load_session_samples(&tx, session_key)?);
+            let points = config.sparkline_points.min(MAX_SPARKLINE_POINTS) as usize;
+            rates[rates.len().saturating_sub(points)..]
+                .iter()
+                .map(|&(ts_ms, tokens_per_s)| RatePoint {
+                    ts_ms,
+                    tokens_per_s,
+                    annotation: None,
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let rollups = config
+        .rollup_windows
+        .iter()
+        .map(|w| labeled_rollup(&tx, &w.label, now - w.duration_ms, now))
+        .collect();
+
+    let mut active_sessions: Vec<ActiveSession> = load_session_totals(&tx)?
+        .into_iter()
+        .filter(|s| now - s.last_ts_ms <= config.active_session_threshold_ms)
+        .map(|s| ActiveSession {
+            session_key: s.session_key,
+            model: s.model,
+            last_ts_ms: s.last_ts_ms,
+            total_tokens: s.total_tokens,
+        })
+        .collect();
+    active_sessions.sort_by_key(|s| std::cmp::Reverse(s.last_ts_ms));
+
+    let cost_today_usd = match cost_config {
+        Some(c) => Some(daily_usage(&tx, c, local_day_start_ms(now, 0))?.1),
+        None => None,
+    };
+
+    Ok(DashboardPack {
+        live,
+        sparkline,
+        rollups,
+        active_sessions,
+        alerts: load_alerts(&tx, now - DAY_MS, now)?,
+        health: health_status(&tx, db_path, false)?,
+        cost_today_usd,
+    })
+}
+
+// Past this, the last generation has most likely finished or paused.
+const IN_FLIGHT_MAX_AGE_MS: i64 = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightEstimate {
+    session_key: String,
+    out_tokens_per_s: f64,
+    estimated_tokens_since_last_sample: f64,
+    last_sample_age_ms: i64,
+}
+
+// Output tokens probably generated since the latest sample, extrapolated at the live
+// output rate. None without a recent sample or a usable rate.
+#[tauri::command]
+fn get_tokens_in_flight(db_path: Option<String>) -> Result<Option<InFlightEstimate>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let Ok(live) = live_metrics(&conn) else {
+        return Ok(None);
+    };
+    let last_sample_age_ms = now_ms() - live.ts_ms;
+    if last_sample_age_ms > IN_FLIGHT_MAX_AGE_MS {
+        return Ok(None);
+    }
+    let (Some(session_key), Some(out_tokens_per_s)) = (live.session_key, live.out_tokens_per_s)
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(InFlightEstimate {
+        session_key,
+        out_tokens_per_s,
+        estimated_tokens_since_last_sample: out_tokens_per_s * last_sample_age_ms.max(0) as f64
+            / 1000.0,
+        last_sample_age_ms,
+    }))
+}
+
+// Longer gaps between samples are idle time, not generation.
+const LATENCY_MAX_INTERVAL_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelLatencyProfile {
+    model: String,
+    mean_sample_interval_ms: f64,
+    min_sample_interval_ms: i64,
+    max_sample_interval_ms: i64,
+    // number of intervals measured
+    sample_count: i64,
+}
+
+#[tauri::command]
+fn get_model_latency_profile(db_path: Option<String>) -> Result<Vec<ModelLatencyProfile>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut by_model: std::collections::BTreeMap<String, Vec<i64>> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        // Like the performance profile, an interval belongs to the later sample's model.
+        for w in session.windows(2) {
+            let interval = w[1].ts_ms - w[0].ts_ms;
+            if let Some(m) = w[1].model.as_deref() {
+                if interval <= LATENCY_MAX_INTERVAL_MS {
+                    by_model.entry(m.to_string()).or_default().push(interval);
+                }
+            }
+        }
+    }
+
+    Ok(by_model
+        .into_iter()
+        .map(|(model, intervals)| ModelLatencyProfile {
+            model,
+            mean_sample_interval_ms: intervals.iter().sum::<i64>() as f64 / intervals.len() as f64,
+            min_sample_interval_ms: intervals.iter().copied().min().unwrap_or(0),
+            max_sample_interval_ms: intervals.iter().copied().max().unwrap_or(0),
+            sample_count: intervals.len() as i64,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EfficiencyTimePoint {
+    ts_ms: i64,
+    rolling_output_input_ratio: Option<f64>,
+}
+
+// Output/input delta ratio across each sample's trailing window (shorter at the start,
+// as in `counter_trend`). None where input didn't grow across the window.
+#[tauri::command]
+fn get_session_token_efficiency_over_time(
+    session_key: String,
+    window_size: usize,
+    db_path: Option<String>,
+) -> Result<Vec<EfficiencyTimePoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let window = window_size.clamp(MIN_TREND_WINDOW, MAX_TREND_WINDOW);
+    Ok(samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let first = &samples[(i + 1).saturating_sub(window)];
+            let ratio = match (
+                counter_delta(first.input_tokens, s.input_tokens),
+                counter_delta(first.output_tokens, s.output_tokens),
+            ) {
+                (Some(input), Some(output)) if input > 0 => Some(output as f64 / input as f64),
+                _ => None,
+            };
+            EfficiencyTimePoint {
+                ts_ms: s.ts_ms,
+                rolling_output_input_ratio: ratio,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourlyNetworkUsage {
+    hour_of_day: u8,
+    total_rx_bytes: i64,
+    total_tx_bytes: i64,
+    mean_rx_bytes_per_s: f64,
+    // sample pairs that landed in this hour
+    sample_count: i64,
+}
+
+// Per-pair byte deltas summed by the local hour of the later sample; always 24 rows.
+#[tauri::command]
+fn get_network_bytes_by_hour(
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<HourlyNetworkUsage>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let mut out: Vec<HourlyNetworkUsage> = (0..24)
+        .map(|h| HourlyNetworkUsage {
+            hour_of_day: h,
+            total_rx_bytes: 0,
+            total_tx_bytes: 0,
+            mean_rx_bytes_per_s: 0.0,
+            sample_count: 0,
+        })
+        .collect();
+    let mut rx_rates: Vec<Vec<f64>> = vec![Vec::new(); 24];
+    for session in load_samples_by_session(&conn)? {
+        for w in session.windows(2) {
+            let (a, b) = (&w[0], &w[1]);
+            let hour = ((b.ts_ms + offset_ms).rem_euclid(DAY_MS) / HOUR_MS) as usize;
+            let bucket = &mut out[hour];
+            bucket.total_rx_bytes += counter_delta(a.net_rx_bytes, b.net_rx_bytes).unwrap_or(0);
+            bucket.total_tx_bytes += counter_delta(a.net_tx_bytes, b.net_tx_bytes).unwrap_or(0);
+            bucket.sample_count += 1;
+            let dt_s = (b.ts_ms - a.ts_ms) as f64 / 1000.0;
+            if let Some(r) = counter_rate(a.net_rx_bytes, b.net_rx_bytes, dt_s) {
+                rx_rates[hour].push(r);
+            }
+        }
+    }
+    for (bucket, rates) in out.iter_mut().zip(&rx_rates) {
+        bucket.mean_rx_bytes_per_s = mean(rates).unwrap_or(0.0);
+    }
+    Ok(out)
+}
+
+const MIN_SATURATING_SESSIONS: i64 = 3;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSaturationRate {
+    model: String,
+    mean_time_to_saturation_ms: f64,
+    sessions_that_saturated: i64,
+    sessions_total: i64,
+    saturation_rate_pct: f64,
+}
+
+// Per model (a session counts for its latest model): how often sessions reach
+// `saturation_pct` and how long after their first sample. Models with fewer than
+// MIN_SATURATING_SESSIONS saturating sessions are left out; their mean isn't meaningful.
+#[tauri::command]
+fn get_model_context_saturation_rates(
+    saturation_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<ModelSaturationRate>, String> {
+    if !(1..=100).contains(&saturation_pct) {
+        return Err(format!(
+            "saturation_pct must be between 1 and 100, got {}",
+            saturation_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // model -> (sessions_total, times to saturation)
+    let mut by_model: std::collections::BTreeMap<String, (i64, Vec<i64>)> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        let (Some(first), Some(model)) = (
+            session.first(),
+            session.last().and_then(|s| s.model.clone()),
+        ) else {
+            continue;
+        };
+        let entry = by_model.entry(model).or_default();
+        entry.0 += 1;
+        if let Some(s) = session
+            .iter()
+            .find(|s| s.percent_used.is_some_and(|p| p >= saturation_pct))
+        {
+            entry.1.push(s.ts_ms - first.ts_ms);
+        }
+    }
+
+    Ok(by_model
+        .into_iter()
+        .filter(|(_, (_, times))| times.len() as i64 >= MIN_SATURATING_SESSIONS)
+        .map(|(model, (sessions_total, times))| {
+            let sessions_that_saturated = times.len() as i64;
+            ModelSaturationRate {
+                model,
+                mean_time_to_saturation_ms: times.iter().sum::<i64>() as f64
+                    / sessions_that_saturated as f64,
+                sessions_that_saturated,
+                sessions_total,
+                saturation_rate_pct: sessions_that_saturated as f64 / sessions_total as f64 * 100.0,
+            }
+        })
+        .collect())
+}
+
+// (label, first local hour, end hour exclusive)
+const TIME_OF_DAY_PERIODS: [(&str, i64, i64); 4] = [
+    ("night", 0, 6),
+    ("morning", 6, 12),
+    ("afternoon", 12, 18),
+    ("evening", 18, 24),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeOfDayCost {
+    period: String,
+    total_tokens: i64,
+    estimated_cost_usd: f64,
+    pct_of_total_cost: f64,
+}
+
+// Pair deltas priced by the later sample's model and split by its local hour.
+// Without `start_ms`/`end_ms` every sample is included.
+#[tauri::command]
+fn get_cost_attribution_by_time_of_day(
+    cost_config: CostConfig,
+    tz_offset_minutes: i32,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<TimeOfDayCost>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let mut totals = [(0i64, 0.0f64); 4];
+    for session in load_samples_by_session_in_range(
+        &conn,
+        None,
+        start_ms.unwrap_or(i64::MIN),
+        end_ms.unwrap_or(i64::MAX),
+    )? {
+        for w in session.windows(2) {
+            let (a, b) = (&w[0], &w[1]);
+            let hour = (b.ts_ms + offset_ms).rem_euclid(DAY_MS) / HOUR_MS;
+            let Some(i) = TIME_OF_DAY_PERIODS
+                .iter()
+                .position(|(_, from, to)| (*from..*to).contains(&hour))
+            else {
+                continue;
+            };
+            totals[i].0 += counter_delta(a.total_tokens, b.total_tokens).unwrap_or(0);
+            totals[i].1 += cost_config
+                .cost_usd(
+                    b.model.as_deref(),
+                    counter_delta(a.input_tokens, b.input_tokens).unwrap_or(0),
+                    counter_delta(a.output_tokens, b.output_tokens).unwrap_or(0),
+                )
+                .unwrap_or(0.0);
+        }
+    }
+
+    let total_cost: f64 = totals.iter().map(|(_, c)| c).sum();
+    Ok(TIME_OF_DAY_PERIODS
+        .iter()
+        .zip(totals)
+        .map(
+            |((period, _, _), (total_tokens, estimated_cost_usd))| TimeOfDayCost {
+                period: period.to_string(),
+                total_tokens,
+                estimated_cost_usd,
+                pct_of_total_cost: if total_cost > 0.0 {
+                    estimated_cost_usd / total_cost * 100.0
+                } else {
+                    0.0
+                },
+            },
+        )
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstResponseLatency {
+    session_key: String,
+    // model of the most recent sample
+    model: Option<String>,
+    first_nonzero_output_token_ms: Option<i64>,
+    session_start_ms: i64,
+    latency_ms: Option<i64>,
+}
+
+// Time from each session's first sample to its first sample with output, slowest first;
+// sessions that never produced output go last.
+#[tauri::command]
+fn get_session_first_response_latency(
+    db_path: Option<String>,
+) -> Result<Vec<FirstResponseLatency>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out: Vec<FirstResponseLatency> = load_samples_by_session(&conn)?
+        .into_iter()
+        .filter_map(|session| {
+            let (first, last) = (session.first()?, session.last()?);
+            let first_nonzero_output_token_ms = session
+                .iter()
+                .find(|s| s.output_tokens.is_some_and(|o| o > 0))
+                .map(|s| s.ts_ms);
+            Some(FirstResponseLatency {
+                session_key: first.session_key.clone().unwrap_or_default(),
+                model: last.model.clone(),
+                first_nonzero_output_token_ms,
+                session_start_ms: first.ts_ms,
+                latency_ms: first_nonzero_output_token_ms.map(|t| t - first.ts_ms),
+            })
+        })
+        .collect();
+    // `Option` orders None first, so reversing puts it last.
+    out.sort_by_key(|l| std::cmp::Reverse(l.latency_ms));
+    Ok(out)
+}
+
+const MAX_TOP_COST_HOURS: u32 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopCostHour {
+    hour_start_ms: i64,
+    // UTC
+    hour_label: String,
+    total_tokens: i64,
+    estimated_cost_usd: f64,
+    active_sessions: i64,
+}
+
+// The `n` most expensive UTC clock hours. Like `daily_usage`, each session contributes its
+// first-to-last delta within the hour, priced by its last model in that hour.
+#[tauri::command]
+fn get_top_cost_hours(
+    n: u32,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<TopCostHour>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut by_hour: HashMap<i64, TopCostHour> = HashMap::new();
+    for session in load_samples_by_session(&conn)? {
+        for chunk in
+            session.chunk_by(|a, b| a.ts_ms.div_euclid(HOUR_MS) == b.ts_ms.div_euclid(HOUR_MS))
+        {
+            let (f, l) = (&chunk[0], &chunk[chunk.len() - 1]);
+            let hour_start_ms = f.ts_ms.div_euclid(HOUR_MS) * HOUR_MS;
+            let hour = by_hour.entry(hour_start_ms).or_insert_with(|| TopCostHour {
+                hour_start_ms,
+                hour_label: local_hour_label(hour_start_ms, 0),
+                total_tokens: 0,
+                estimated_cost_usd: 0.0,
+                active_sessions: 0,
+            });
+            hour.total_tokens += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+            hour.estimated_cost_usd += cost_config
+                .cost_usd(
+                    l.model.as_deref(),
+                    counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0),
+                    counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0),
+                )
+                .unwrap_or(0.0);
+            hour.active_sessions += 1;
+        }
+    }
+
+    let mut out: Vec<TopCostHour> = by_hour.into_values().collect();
+    out.sort_by(|a, b| {
+        b.estimated_cost_usd
+            .total_cmp(&a.estimated_cost_usd)
+            .then(a.hour_start_ms.cmp(&b.hour_start_ms))
+    });
+    out.truncate(n.min(MAX_TOP_COST_HOURS) as usize);
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionRankMetric {
+    TotalTokens,
+    Duration,
+    PeakTokensPerS,
+    CostUsd,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PercentileRank {
+    session_key: String,
+    value: f64,
+    // 1 = lowest value; ties share the highest rank
+    rank: i64,
+    total_sessions: i64,
+    percentile: f64,
+}
+
+// Where one session falls among all sessions for `metric`. `cost_config` is required for
+// `CostUsd`; sessions without a usable value are left out of the ranking.
+#[tauri::command]
+fn get_session_percentile_rank(
+    session_key: String,
+    metric: SessionRankMetric,
+    cost_config: Option<CostConfig>,
+    db_path: Option<String>,
+) -> Result<Option<PercentileRank>, String> {
+    if matches!(metric, SessionRankMetric::CostUsd) && cost_config.is_none() {
+        return Err("cost_config is required for CostUsd".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let values: Vec<(String, f64)> = match metric {
+        SessionRankMetric::PeakTokensPerS => load_samples_by_session(&conn)?
+            .into_iter()
+            .filter_map(|session| {
+                let peak = pair_rates(&session)
+                    .into_iter()
+                    .map(|(_, r)| r)
+                    .max_by(f64::total_cmp)?;
+                Some((session[0].session_key.clone()?, peak))
+            })
+            .collect(),
+        _ => load_session_totals(&conn)?
+            .into_iter()
+            .filter_map(|t| {
+                let value = match metric {
+                    SessionRankMetric::TotalTokens => t.total_tokens? as f64,
+                    SessionRankMetric::Duration => (t.last_ts_ms - t.first_ts_ms) as f64,
+                    _ => cost_config.as_ref()?.cost_usd(
+                        t.model.as_deref(),
+                        t.input_tokens?,
+                        t.output_tokens?,
+                    )?,
+                };
+                Some((t.session_key, value))
+            })
+            .collect(),
+    };
+
+    let Some(&(_, value)) = values.iter().find(|(k, _)| *k == session_key) else {
+        return Ok(None);
+    };
+    let total_sessions = values.len() as i64;
+    let rank = values.iter().filter(|(_, v)| *v <= value).count() as i64;
+    Ok(Some(PercentileRank {
+        session_key,
+        value,
+        rank,
+        total_sessions,
+        percentile: rank as f64 / total_sessions as f64 * 100.0,
+    }))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccumulationPoint {
+    ts_ms: i64,
+    total_tokens: Option<i64>,
+    // share of the session's largest total_tokens
+    pct_of_session_final: Option<f64>,
+}
+
+#[tauri::command]
+fn get_token_accumulation_curve(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<AccumulationPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let max = samples
+        .iter()
+        .filter_map(|s| s.total_tokens)
+        .max()
+        .filter(|&m| m > 0);
+    Ok(samples
+        .iter()
+        .map(|s| AccumulationPoint {
+            ts_ms: s.ts_ms,
+            total_tokens: s.total_tokens,
+            pct_of_session_final: s
+                .total_tokens
+                .zip(max)
+                .map(|(t, m)| t as f64 / m as f64 * 100.0),
+        })
+        .collect())
+}
+
+// Sum of each named session's own first-to-last deltas; the span runs from the earliest first
+// sample to the latest last sample. A counter stays `None` only if no session has a usable delta.
+#[tauri::command]
+fn get_multi_session_rollup(
+    session_keys: Vec<String>,
+    window_label: String,
+    db_path: Option<String>,
+) -> Result<Rollup, String> {
+    if session_keys.is_empty() {
+        return Err("session_keys must not be empty".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut rollup = Rollup {
+        window_label,
+        start_ts_ms: i64::MAX,
+        end_ts_ms: i64::MIN,
+        input_tokens: None,
+        output_tokens: None,
+        total_tokens: None,
+        net_rx_bytes: None,
+        net_tx_bytes: None,
+        estimated_cost_usd: None,
+        source_count: 1,
+    };
+    let add = |acc: &mut Option<i64>, d: Option<i64>| {
+        if let Some(d) = d {
+            *acc = Some(acc.unwrap_or(0) + d);
+        }
+    };
+    let keys: std::collections::BTreeSet<String> = session_keys.into_iter().collect();
+    for key in &keys {
+        let samples = load_session_samples(&conn, key)?;
+        let (Some(f), Some(l)) = (samples.first(), samples.last()) else {
+            continue;
+        };
+        rollup.start_ts_ms = rollup.start_ts_ms.min(f.ts_ms);
+        rollup.end_ts_ms = rollup.end_ts_ms.max(l.ts_ms);
+        add(
+            &mut rollup.input_tokens,
+            counter_delta(f.input_tokens, l.input_tokens),
+        );
+        add(
+            &mut rollup.output_tokens,
+            counter_delta(f.output_tokens, l.output_tokens),
+        );
+        add(
+            &mut rollup.total_tokens,
+            counter_delta(f.total_tokens, l.total_tokens),
+        );
+        add(
+            &mut rollup.net_rx_bytes,
+            counter_delta(f.net_rx_bytes, l.net_rx_bytes),
+        );
+        add(
+            &mut rollup.net_tx_bytes,
+            counter_delta(f.net_tx_bytes, l.net_tx_bytes),
+        );
+    }
+    if rollup.start_ts_ms > rollup.end_ts_ms {
+        return Err("no samples for the given sessions".to_string());
+    }
+    Ok(rollup)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFillCost {
+    session_key: String,
+    model: Option<String>,
+    // largest context window seen in the session
+    context_tokens: i64,
+    tokens_consumed: i64,
+    fill_count: f64,
+    cost_usd: f64,
+    cost_per_fill_usd: f64,
+}
+
+// Session cost per "full context window" of tokens consumed. Sessions without a context size,
+// without token growth or without a matching price are skipped.
+#[tauri::command]
+fn get_cost_per_context_window_fill(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<ContextFillCost>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        let (f, l) = (&session[0], &session[session.len() - 1]);
+        let Some(session_key) = l.session_key.clone() else {
+            continue;
+        };
+        let Some(context_tokens) = session
+            .iter()
+            .filter_map(|s| s.context_tokens)
+            .max()
+            .filter(|&c| c > 0)
+        else {
+            continue;
+        };
+        let Some(tokens_consumed) =
+            counter_delta(f.total_tokens, l.total_tokens).filter(|&t| t > 0)
+        else {
+            continue;
+        };
+        let Some(cost_usd) = cost_config.cost_usd(
+            l.model.as_deref(),
+            counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0),
+            counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0),
+        ) else {
+            continue;
+        };
+        let fill_count = tokens_consumed as f64 / context_tokens as f64;
+        out.push(ContextFillCost {
+            session_key,
+            model: l.model.clone(),
+            context_tokens,
+            tokens_consumed,
+            fill_count,
+            cost_usd,
+            cost_per_fill_usd: cost_usd / fill_count,
+        });
+    }
+    Ok(out)
+}
+
+const MAX_ROLLING_WINDOW_SAMPLES: u32 = 200;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingTotal {
+    ts_ms: i64,
+    // `None` when no sample in the window has a usable delta
+    rolling_token_sum: Option<i64>,
+}
+
+// Moving sum of per-sample total_tokens growth over the preceding `window_samples` samples,
+// across all sessions, for the last `window_samples * 2` samples. Each delta is taken against the
+// previous sample of the same session; counter resets contribute nothing.
+#[tauri::command]
+fn get_rolling_total_tokens(
+    window_samples: u32,
+    db_path: Option<String>,
+) -> Result<Vec<RollingTotal>, String> {
+    if window_samples == 0 {
+        return Err("window_samples must be positive".to_string());
+    }
+    let window = window_samples.min(MAX_ROLLING_WINDOW_SAMPLES) as usize;
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // The oldest reported point still needs `window - 1` samples before it.
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH deltas AS (
+                SELECT ts_ms, session_key,
+                       total_tokens - LAG(total_tokens) OVER (PARTITION BY session_key ORDER BY ts_ms) AS delta
+                FROM samples
+            )
+            SELECT ts_ms, delta
+            FROM deltas
+            ORDER BY ts_ms DESC, session_key DESC
+            LIMIT ?1
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([(window * 3 - 1) as i64], |r| {
+            Ok((r.get::<_, i64>(0)?, r.get::<_, Option<i64>>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let mut samples = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    samples.reverse();
+
+    let skip = samples.len().saturating_sub(window * 2);
+    Ok((skip..samples.len())
+        .map(|i| {
+            let from = (i + 1).saturating_sub(window);
+            let deltas: Vec<i64> = samples[from..=i]
+                .iter()
+                .filter_map(|&(_, d)| d.filter(|&d| d >= 0))
+                .collect();
+            RollingTotal {
+                ts_ms: samples[i].0,
+                rolling_token_sum: (!deltas.is_empty()).then(|| deltas.iter().sum()),
+            }
+        })
+        .collect())
+}
+
+const WRITE_LATENCY_SAMPLES: i64 = 100;
+const WRITE_DELAY_MS: i64 = 5000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteLatencyStats {
+    // over the recent samples that are not in the future
+    mean_latency_ms: f64,
+    max_latency_ms: i64,
+    // ts_ms ahead of now: collector clock skew
+    samples_with_future_ts: i64,
+    // older than WRITE_DELAY_MS at query time: insertion delay
+    delayed_samples: i64,
+}
+
+// How far the newest WRITE_LATENCY_SAMPLES samples lag the wall clock at query time.
+#[tauri::command]
+fn get_sample_write_latency_stats(db_path: Option<String>) -> Result<WriteLatencyStats, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare("SELECT ts_ms FROM samples ORDER BY ts_ms DESC LIMIT ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([WRITE_LATENCY_SAMPLES], |r| r.get::<_, i64>(0))
+        .map_err(|e| e.to_string())?;
+    let ts = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let now = now_ms();
+    let latencies: Vec<i64> = ts.iter().map(|t| now - t).filter(|&l| l >= 0).collect();
+    Ok(WriteLatencyStats {
+        mean_latency_ms: mean(&latencies.iter().map(|&l| l as f64).collect::<Vec<_>>())
+            .unwrap_or(0.0),
+        max_latency_ms: latencies.iter().copied().max().unwrap_or(0),
+        samples_with_future_ts: (ts.len() - latencies.len()) as i64,
+        delayed_samples: latencies.iter().filter(|&&l| l > WRITE_DELAY_MS).count() as i64,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyPeak {
+    date_label: String,
+    // all `None` on days without a usable sample pair
+    peak_tokens_per_s: Option<f64>,
+    peak_ts_ms: Option<i64>,
+    session_key: Option<String>,
+}
+
+// Highest adjacent-pair tokens/s of each local day; a pair belongs to the day of its later sample.
+#[tauri::command]
+fn get_daily_peak_tokens_per_s(
+    days_back: u32,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<DailyPeak>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Same local-day window as get_unique_session_count_by_day.
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let days = days_back.min(MAX_DAYS_BACK) as i64;
+    let end = now_ms();
+    let today = (end + offset_ms).div_euclid(DAY_MS);
+    let first_day = today - days + 1;
+
+    let mut peaks: HashMap<i64, (f64, i64, Option<String>)> = HashMap::new();
+    for session in
+        load_samples_by_session_in_range(&conn, None, first_day * DAY_MS - offset_ms, end)?
+    {
+        for (ts, rate) in pair_rates(&session) {
+            let day = (ts + offset_ms).div_euclid(DAY_MS);
+            let peak = peaks.entry(day).or_insert((rate, ts, None));
+            if rate >= peak.0 {
+                *peak = (rate, ts, session[0].session_key.clone());
+            }
+        }
+    }
+
+    Ok((first_day..=today)
+        .map(|day| {
+            let peak = peaks.remove(&day);
+            DailyPeak {
+                date_label: local_date_label(day * DAY_MS - offset_ms, tz_offset_minutes),
+                peak_tokens_per_s: peak.as_ref().map(|p| p.0),
+                peak_ts_ms: peak.as_ref().map(|p| p.1),
+                session_key: peak.and_then(|p| p.2),
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostVariance {
+    mean_cost_usd: f64,
+    stddev_cost_usd: f64,
+    // stddev / mean; 0 when the mean is 0
+    cv: f64,
+    min_cost_usd: f64,
+    max_cost_usd: f64,
+    session_count: i64,
+}
+
+// Spread of per-session cost estimates. Sessions without a matching price are left out; with no
+// priced sessions every field is 0.
+#[tauri::command]
+fn get_session_cost_variance(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<CostVariance, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let costs: Vec<f64> = load_session_totals(&conn)?
+        .iter()
+        .filter_map(|t| {
+            cost_config.cost_usd(
+                t.model.as_deref(),
+                t.input_tokens.unwrap_or(0),
+                t.output_tokens.unwrap_or(0),
+            )
+        })
+        .collect();
+    let mean_cost_usd = mean(&costs).unwrap_or(0.0);
+    let stddev_cost_usd = std_dev(&costs).unwrap_or(0.0);
+    Ok(CostVariance {
+        mean_cost_usd,
+        stddev_cost_usd,
+        cv: if mean_cost_usd > 0.0 {
+            stddev_cost_usd / mean_cost_usd
+        } else {
+            0.0
+        },
+        min_cost_usd: costs.iter().copied().reduce(f64::min).unwrap_or(0.0),
+        max_cost_usd: costs.iter().copied().reduce(f64::max).unwrap_or(0.0),
+        session_count: costs.len() as i64,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScatterPoint {
+    session_key: String,
+    total_tokens: i64,
+    // 0 when `cost_config_missing`
+    estimated_cost_usd: f64,
+    cost_config_missing: bool,
+    duration_ms: i64,
+    model: Option<String>,
+}
+
+// One point per session, including sessions whose model has no price.
+#[tauri::command]
+fn get_tokens_vs_cost_scatter_data(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<ScatterPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    Ok(load_session_totals(&conn)?
+        .into_iter()
+        .map(|t| {
+            let cost = cost_config.cost_usd(
+                t.model.as_deref(),
+                t.input_tokens.unwrap_or(0),
+                t.output_tokens.unwrap_or(0),
+            );
+            ScatterPoint {
+                session_key: t.session_key,
+                total_tokens: t.total_tokens.unwrap_or(0),
+                estimated_cost_usd: cost.unwrap_or(0.0),
+                cost_config_missing: cost.is_none(),
+                duration_ms: t.last_ts_ms - t.first_ts_ms,
+                model: t.model,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FractionPoint {
+    ts_ms: i64,
+    // shares of the cumulative total_tokens; `None` when the total is missing or 0
+    input_fraction: Option<f64>,
+    output_fraction: Option<f64>,
+}
+
+#[tauri::command]
+fn get_session_input_token_fraction(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<FractionPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    Ok(load_session_samples(&conn, &session_key)?
+        .iter()
+        .map(|s| {
+            let total = s.total_tokens.filter(|&t| t != 0);
+            let fraction = |part: Option<i64>| part.zip(total).map(|(p, t)| p as f64 / t as f64);
+            FractionPoint {
+                ts_ms: s.ts_ms,
+                input_fraction: fraction(s.input_tokens),
+                output_fraction: fraction(s.output_tokens),
+            }
+        })
+        .collect())
+}
+
+const MAX_MONTHS_BACK: u32 = 120;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyModelShare {
+    // "YYYY-MM", UTC
+    month_label: String,
+    model: String,
+    tokens: i64,
+    pct_of_month: f64,
+}
+
+// Per-model token share of each UTC calendar month, the current month being the last of
+// `months_back`. Every model seen in the range gets a row for every month. Each session
+// contributes its first-to-last delta within the month, credited to its last model there.
+#[tauri::command]
+fn get_model_usage_share_over_time(
+    months_back: u32,
+    db_path: Option<String>,
+) -> Result<Vec<MonthlyModelShare>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let month_of = |ts_ms: i64| {
+        let (y, m, _) = civil_from_days(ts_ms.div_euclid(DAY_MS));
+        y * 12 + m as i64 - 1
+    };
+    let end = now_ms();
+    let last_month = month_of(end);
+    let first_month = last_month - months_back.min(MAX_MONTHS_BACK) as i64 + 1;
+    let start = days_from_civil(
+        first_month.div_euclid(12),
+        first_month.rem_euclid(12) as u32 + 1,
+        1,
+    ) * DAY_MS;
+
+    let mut tokens: HashMap<(i64, String), i64> = HashMap::new();
+    let mut models: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    for session in load_samples_by_session_in_range(&conn, None, start, end)? {
+        for chunk in session.chunk_by(|a, b| month_of(a.ts_ms) == month_of(b.ts_ms)) {
+            let (f, l) = (&chunk[0], &chunk[chunk.len() - 1]);
+            let Some(model) = l.model.clone() else {
+                continue;
+            };
+            *tokens
+                .entry((month_of(l.ts_ms), model.clone()))
+                .or_default() += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+            models.insert(model);
+        }
+    }
+
+    let mut out = Vec::new();
+    for month in first_month..=last_month {
+        let month_label = format!(
+            "{:04}-{:02}",
+            month.div_euclid(12),
+            month.rem_euclid(12) + 1
+        );
+        let month_tokens: Vec<i64> = models
+            .iter()
+            .map(|m| tokens.get(&(month, m.clone())).copied().unwrap_or(0))
+            .collect();
+        let month_total: i64 = month_tokens.iter().sum();
+        for (model, t) in models.iter().zip(month_tokens) {
+            out.push(MonthlyModelShare {
+                month_label: month_label.clone(),
+                model: model.clone(),
+                tokens: t,
+                pct_of_month: if month_total > 0 {
+                    t as f64 / month_total as f64 * 100.0
+                } else {
+                    0.0
+                },
+            });
+        }
+    }
+    Ok(out)
+}
+
+const ACTIVE_HOUR_MAX_GAP_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokensPerActiveHour {
+    total_tokens: i64,
+    active_hours: f64,
+    // 0 without any active time
+    tokens_per_active_hour: f64,
+}
+
+// Token throughput per hour of actual activity: session time counts only across sample gaps of
+// at most ACTIVE_HOUR_MAX_GAP_MS.
+#[tauri::command]
+fn get_average_tokens_per_active_hour(
+    db_path: Option<String>,
+) -> Result<TokensPerActiveHour, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut total_tokens = 0;
+    let mut active_ms = 0;
+    for session in load_samples_by_session(&conn)? {
+        let (f, l) = (&session[0], &session[session.len() - 1]);
+        total_tokens += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+        active_ms += session
+            .windows(2)
+            .map(|w| w[1].ts_ms - w[0].ts_ms)
+            .filter(|&gap| gap <= ACTIVE_HOUR_MAX_GAP_MS)
+            .sum::<i64>();
+    }
+    let active_hours = active_ms as f64 / HOUR_MS as f64;
+    Ok(TokensPerActiveHour {
+        total_tokens,
+        active_hours,
+        tokens_per_active_hour: if active_ms > 0 {
+            total_tokens as f64 / active_hours
+        } else {
+            0.0
+        },
+    })
+}
+
+const MIN_FILL_RATE_SAMPLES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFillRate {
+    model: String,
+    // percent_used points gained per minute of session time
+    mean_pct_per_minute: f64,
+    sessions_analyzed: i64,
+}
+
+// Mean context fill speed per model, over sessions with at least MIN_FILL_RATE_SAMPLES samples.
+// A session counts for its latest model.
+#[tauri::command]
+fn get_context_fill_rate_by_model(db_path: Option<String>) -> Result<Vec<ModelFillRate>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut by_model: std::collections::BTreeMap<String, Vec<f64>> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        if session.len() < MIN_FILL_RATE_SAMPLES {
+            continue;
+        }
+        let (f, l) = (&session[0], &session[session.len() - 1]);
+        let minutes = (l.ts_ms - f.ts_ms) as f64 / 60_000.0;
+        let (Some(model), Some(p0), Some(p1)) = (l.model.clone(), f.percent_used, l.percent_used)
+        else {
+            continue;
+        };
+        if minutes > 0.0 {
+            by_model
+                .entry(model)
+                .or_default()
+                .push((p1 - p0) as f64 / minutes);
+        }
+    }
+
+    Ok(by_model
+        .into_iter()
+        .map(|(model, rates)| ModelFillRate {
+            model,
+            mean_pct_per_minute: mean(&rates).unwrap_or(0.0),
+            sessions_analyzed: rates.len() as i64,
+        })
+        .collect())
+}
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayOfWeekCost {
+    day_name: String,
+    // ISO: 1 = Monday .. 7 = Sunday
+    day_number: u8,
+    // per local day of this weekday that has data
+    mean_tokens: f64,
+    mean_cost_usd: f64,
+    sample_weeks: i64,
+}
+
+// Usage per local weekday, Monday first. Each local day is measured like `daily_usage`, then the
+// days are averaged per weekday.
+#[tauri::command]
+fn get_cost_by_day_of_week(
+    cost_config: CostConfig,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<DayOfWeekCost>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let day_of = |ts_ms: i64| (ts_ms + offset_ms).div_euclid(DAY_MS);
+    let mut days: HashMap<i64, (i64, f64)> = HashMap::new();
+    for session in load_samples_by_session(&conn)? {
+        for chunk in session.chunk_by(|a, b| day_of(a.ts_ms) == day_of(b.ts_ms)) {
+            let (f, l) = (&chunk[0], &chunk[chunk.len() - 1]);
+            let day = days.entry(day_of(f.ts_ms)).or_default();
+            day.0 += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+            day.1 += cost_config
+                .cost_usd(
+                    l.model.as_deref(),
+                    counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0),
+                    counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0),
+                )
+                .unwrap_or(0.0);
+        }
+    }
+
+    // 1970-01-01 was a Thursday.
+    let mut weekdays = [(0i64, 0.0, 0i64); 7];
+    for (day, (tokens, cost)) in days {
+        let w = &mut weekdays[(day + 3).rem_euclid(7) as usize];
+        w.0 += tokens;
+        w.1 += cost;
+        w.2 += 1;
+    }
+    Ok(weekdays
+        .iter()
+        .enumerate()
+        .map(|(i, &(tokens, cost, sample_weeks))| {
+            let n = sample_weeks.max(1) as f64;
+            DayOfWeekCost {
+                day_name: WEEKDAY_NAMES[i].to_string(),
+                day_number: i as u8 + 1,
+                mean_tokens: tokens as f64 / n,
+                mean_cost_usd: cost / n,
+                sample_weeks,
+            }
+        })
+        .collect())
+}
+
+const MIN_BURST_SESSION_MS: i64 = 5 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurstFrequency {
+    session_key: String,
+    bursts_per_hour: f64,
+    total_bursts: i64,
+    session_duration_hours: f64,
+}
+
+// Bursts are runs of adjacent pairs faster than `threshold_tokens_per_s`, split like the
+// burst_periods of get_complete_session_profile. Sessions under MIN_BURST_SESSION_MS are skipped.
+#[tauri::command]
+fn get_token_burst_frequency(
+    threshold_tokens_per_s: f64,
+    db_path: Option<String>,
+) -> Result<Vec<BurstFrequency>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        let duration_ms = session[session.len() - 1].ts_ms - session[0].ts_ms;
+        let Some(session_key) = session[0].session_key.clone() else {
+            continue;
+        };
+        if duration_ms < MIN_BURST_SESSION_MS {
+            continue;
+        }
+        let mut total_bursts = 0;
+        let mut prev_bursting = false;
+        for w in session.windows(2) {
+            let bursting = pair_rate(&w[0], &w[1]).is_some_and(|r| r > threshold_tokens_per_s);
+            if bursting && !prev_bursting {
+                total_bursts += 1;
+            }
+            prev_bursting = bursting;
+        }
+        let session_duration_hours = duration_ms as f64 / HOUR_MS as f64;
+        out.push(BurstFrequency {
+            session_key,
+            bursts_per_hour: total_bursts as f64 / session_duration_hours,
+            total_bursts,
+            session_duration_hours,
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeduplicationReport {
+    total_samples: i64,
+    mergeable_samples: i64,
+    reduction_pct: f64,
+    affected_sessions: i64,
+}
+
+// Dry run of `compact_session_samples` over every session: counts the samples it would delete,
+// i.e. those followed by another sample of the same session within `merge_window_ms`.
+#[tauri::command]
+fn get_sample_deduplication_report(
+    merge_window_ms: i64,
+    db_path: Option<String>,
+) -> Result<DeduplicationReport, String> {
+    if merge_window_ms <= 0 {
+        return Err("merge_window_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let (total_samples, mergeable_samples, affected_sessions): (i64, i64, i64) = timed_query(
+        &conn,
+        r#"
+            WITH next AS (
+                SELECT session_key, ts_ms,
+                       session_key IS NOT NULL
+                           AND LEAD(ts_ms) OVER (PARTITION BY session_key ORDER BY ts_ms) - ts_ms <= ?1
+                           AS mergeable
+                FROM samples
+            )
+            SELECT COUNT(*),
+                   COUNT(CASE WHEN mergeable THEN 1 END),
+                   COUNT(DISTINCT CASE WHEN mergeable THEN session_key END)
+            FROM next
+            "#,
+        [merge_window_ms],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )?;
+    Ok(DeduplicationReport {
+        total_samples,
+        mergeable_samples,
+        reduction_pct: if total_samples > 0 {
+            mergeable_samples as f64 / total_samples as f64 * 100.0
+        } else {
+            0.0
+        },
+        affected_sessions,
+    })
+}
+
+const MAX_HEATMAP_DAYS: u32 = 90;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapCell {
+    date_label: String,
+    hour_of_day: u8,
+    // `None` for hours without samples
+    total_tokens: Option<i64>,
+    sample_count: i64,
+    // total_tokens / the largest cell's total_tokens
+    intensity_normalized: f64,
+}
+
+// days_back x 24 local-hour cells, oldest first. A cell's tokens are the per-session
+// first-to-last deltas within that hour.
+#[tauri::command]
+fn get_multiday_heatmap(
+    days_back: u32,
+    tz_offset_minutes: i32,
+    db_path: Option<String>,
+) -> Result<Vec<HeatmapCell>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // Same local-day window as get_unique_session_count_by_day.
+    let offset_ms = tz_offset_minutes as i64 * 60 * 1000;
+    let days = days_back.min(MAX_HEATMAP_DAYS) as i64;
+    let end = now_ms();
+    let today = (end + offset_ms).div_euclid(DAY_MS);
+    let first_day = today - days + 1;
+
+    let hour_of = |ts_ms: i64| (ts_ms + offset_ms).div_euclid(HOUR_MS);
+    let mut cells: HashMap<i64, (i64, i64)> = HashMap::new();
+    for session in
+        load_samples_by_session_in_range(&conn, None, first_day * DAY_MS - offset_ms, end)?
+    {
+        for chunk in session.chunk_by(|a, b| hour_of(a.ts_ms) == hour_of(b.ts_ms)) {
+            let (f, l) = (&chunk[0], &chunk[chunk.len() - 1]);
+            let cell = cells.entry(hour_of(f.ts_ms)).or_default();
+            cell.0 += counter_delta(f.total_tokens, l.total_tokens).unwrap_or(0);
+            cell.1 += chunk.len() as i64;
+        }
+    }
+    let max_tokens = cells.values().map(|c| c.0).max().unwrap_or(0);
+
+    let mut out = Vec::new();
+    for day in first_day..=today {
+        let date_label = local_date_label(day * DAY_MS - offset_ms, tz_offset_minutes);
+        for hour_of_day in 0..24u8 {
+            let cell = cells.get(&(day * 24 + hour_of_day as i64));
+            out.push(HeatmapCell {
+                date_label: date_label.clone(),
+                hour_of_day,
+                total_tokens: cell.map(|c| c.0),
+                sample_count: cell.map_or(0, |c| c.1),
+                intensity_normalized: match cell {
+                    Some(c) if max_tokens > 0 => c.0 as f64 / max_tokens as f64,
+                    _ => 0.0,
+                },
+            });
+        }
+    }
+    Ok(out)
+}
+
+const MAX_SESSION_AGE_BUCKETS: u32 = 500;
+// 30 days; a wider bucket would hold every session in its first one anyway.
+const MAX_SESSION_AGE_BUCKET_MINUTES: u32 = 30 * 24 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAgeBucket {
+    age_start_min: u32,
+    // exclusive
+    age_end_min: u32,
+    // per session with samples at that age
+    mean_tokens: f64,
+    sample_count: i64,
+}
+
+// Token growth by time since session start. Each adjacent-pair delta lands in the bucket of its
+// later sample; ages from `max_age_minutes` on are ignored.
+#[tauri::command]
+fn get_token_consumption_by_session_age(
+    bucket_minutes: u32,
+    max_age_minutes: u32,
+    db_path: Option<String>,
+) -> Result<Vec<SessionAgeBucket>, String> {
+    if bucket_minutes == 0 {
+        return Err("bucket_minutes must be positive".to_string());
+    }
+    if bucket_minutes > MAX_SESSION_AGE_BUCKET_MINUTES {
+        return Err(format!(
+            "bucket_minutes must be at most {}",
+            MAX_SESSION_AGE_BUCKET_MINUTES
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let bucket_count = max_age_minutes
+        .div_ceil(bucket_minutes)
+        .min(MAX_SESSION_AGE_BUCKETS) as usize;
+    let bucket_ms = bucket_minutes as i64 * 60_000;
+    // (tokens, sessions, samples) per bucket
+    let mut buckets = vec![(0i64, 0i64, 0i64); bucket_count];
+    for session in load_samples_by_session(&conn)? {
+        let start = session[0].ts_ms;
+        let mut seen = vec![false; bucket_count];
+        for (i, s) in session.iter().enumerate() {
+            let age_ms = s.ts_ms - start;
+            let b = (age_ms / bucket_ms) as usize;
+            if age_ms >= max_age_minutes as i64 * 60_000 || b >= bucket_count {
+                break;
+            }
+            if i > 0 {
+                buckets[b].0 +=
+                    counter_delta(session[i - 1].total_tokens, s.total_tokens).unwrap_or(0);
+            }
+            buckets[b].2 += 1;
+            if !seen[b] {
+                seen[b] = true;
+                buckets[b].1 += 1;
+            }
+        }
+    }
+
+    Ok(buckets
+        .into_iter()
+        .enumerate()
+        .map(|(i, (tokens, sessions, samples))| SessionAgeBucket {
+            // Bucket bounds can pass u32::MAX when `max_age_minutes` is near it, so they saturate.
+            age_start_min: (i as u32).saturating_mul(bucket_minutes),
+            age_end_min: (i as u32 + 1)
+                .saturating_mul(bucket_minutes)
+                .min(max_age_minutes),
+            mean_tokens: if sessions > 0 {
+                tokens as f64 / sessions as f64
+            } else {
+                0.0
+            },
+            sample_count: samples,
+        })
+        .collect())
+}
+
+const SESSION_STILL_ACTIVE_MS: i64 = 60_000;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EndReason {
+    ContextSaturated,
+    UserTerminated,
+    CollectorCrash,
+    StillActive,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEndReason {
+    reason: EndReason,
+    // rough 0..1 weight of the heuristic
+    confidence: f64,
+}
+
+// Heuristic: a full context ends the session; a recent last sample means it has not ended; tokens
+// still flowing at the last sample point to the collector dying (more so if it wrote nothing
+// after), and a quiet ending to the user closing the session.
+#[tauri::command]
+fn get_session_end_reason(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<SessionEndReason, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let Some(last) = samples.last() else {
+        return Ok(SessionEndReason {
+            reason: EndReason::Unknown,
+            confidence: 0.0,
+        });
+    };
+    let (reason, confidence) = if last.percent_used.is_some_and(|p| p >= SATURATION_PCT) {
+        (EndReason::ContextSaturated, 0.9)
+    } else if now_ms() - last.ts_ms <= SESSION_STILL_ACTIVE_MS {
+        (EndReason::StillActive, 0.9)
+    } else if samples.len() < 2 {
+        (EndReason::Unknown, 0.0)
+    } else if pair_rate(&samples[samples.len() - 2], last).is_some_and(|r| r > 0.0) {
+        let collector_kept_writing: bool = timed_query(
+            &conn,
+            "SELECT EXISTS(SELECT 1 FROM samples WHERE ts_ms > ?1)",
+            [last.ts_ms],
+            |r| r.get(0),
+        )?;
+        let confidence = if collector_kept_writing { 0.4 } else { 0.7 };
+        (EndReason::CollectorCrash, confidence)
+    } else {
+        (EndReason::UserTerminated, 0.6)
+    };
+    Ok(SessionEndReason { reason, confidence })
+}
+
+// Cache reads are billed at roughly a tenth of the input price, so each read token saves ~90%.
+const CACHE_READ_SAVINGS_FACTOR: f64 = 0.9;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachingSavings {
+    cache_read_tokens: i64,
+    cache_creation_tokens: i64,
+    // input tokens served from the cache instead of being reprocessed
+    tokens_saved: i64,
+    cost_saved_usd: f64,
+    cache_efficiency_pct: f64,
+}
+
+// Cache usage over [start_ms, end_ms], optionally for one session, from per-session deltas of the
+// cumulative cache counters. Fails on databases whose samples table predates those columns.
+#[tauri::command]
+fn get_tokens_saved_by_caching(
+    session_key: Option<String>,
+    start_ms: i64,
+    end_ms: i64,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<CachingSavings, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let columns = samples_columns(&conn)?;
+    let missing: Vec<&str> = ["cache_read_tokens", "cache_creation_tokens"]
+        .into_iter()
+        .filter(|c| !columns.iter().any(|col| col.name == *c))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "samples table has no {} column(s); the collector does not record cache usage",
+            missing.join(", ")
+        ));
+    }
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH ranked AS (
+                SELECT session_key, model, cache_read_tokens, cache_creation_tokens,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms ASC) AS rn_first,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms DESC) AS rn_last
+                FROM samples
+                WHERE session_key IS NOT NULL AND ts_ms >= ?1 AND ts_ms <= ?2
+                  AND (?3 IS NULL OR session_key = ?3)
+            )
+            SELECT l.model, f.cache_read_tokens, l.cache_read_tokens,
+                   f.cache_creation_tokens, l.cache_creation_tokens
+            FROM ranked f
+            JOIN ranked l ON l.session_key = f.session_key AND l.rn_last = 1
+            WHERE f.rn_first = 1
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![start_ms, end_ms, session_key], |r| {
+            Ok((
+                r.get::<_, Option<String>>(0)?,
+                counter_delta(r.get(1)?, r.get(2)?).unwrap_or(0),
+                counter_delta(r.get(3)?, r.get(4)?).unwrap_or(0),
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    let sessions = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let (mut cache_read_tokens, mut cache_creation_tokens, mut cost_saved_usd) = (0, 0, 0.0);
+    for (model, read, creation) in sessions {
+        cache_read_tokens += read;
+        cache_creation_tokens += creation;
+        cost_saved_usd += cost_config
+            .cost_usd(model.as_deref(), read, 0)
+            .map_or(0.0, |c| c * CACHE_READ_SAVINGS_FACTOR);
+    }
+    let cached = cache_read_tokens + cache_creation_tokens;
+    Ok(CachingSavings {
+        cache_read_tokens,
+        cache_creation_tokens,
+        tokens_saved: cache_read_tokens,
+        cost_saved_usd,
+        cache_efficiency_pct: if cached > 0 {
+            cache_read_tokens as f64 / cached as f64 * 100.0
+        } else {
+            0.0
+        },
+    })
+}
+
+const MIN_MODEL_PROFILE_SESSIONS: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTokenProfile {
+    model: String,
+    mean_input_fraction: f64,
+    mean_output_fraction: f64,
+    mean_total_tokens_per_session: f64,
+    session_count: i64,
+}
+
+// Per-model input/output mix, averaged over sessions (counted for their latest model) with token
+// growth. Models with fewer than MIN_MODEL_PROFILE_SESSIONS such sessions are left out.
+#[tauri::command]
+fn get_model_input_output_profile(
+    db_path: Option<String>,
+) -> Result<Vec<ModelTokenProfile>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // (input fraction, output fraction, total tokens) per session
+    let mut by_model: std::collections::BTreeMap<String, Vec<(f64, f64, f64)>> =
+        std::collections::BTreeMap::new();
+    for t in load_session_totals(&conn)? {
+        let (Some(model), Some(total)) = (t.model, t.total_tokens.filter(|&t| t > 0)) else {
+            continue;
+        };
+        let total_f = total as f64;
+        by_model.entry(model).or_default().push((
+            t.input_tokens.unwrap_or(0) as f64 / total_f,
+            t.output_tokens.unwrap_or(0) as f64 / total_f,
+            total_f,
+        ));
+    }
+
+    Ok(by_model
+        .into_iter()
+        .filter(|(_, sessions)| sessions.len() >= MIN_MODEL_PROFILE_SESSIONS)
+        .map(|(model, sessions)| {
+            let column = |f: fn(&(f64, f64, f64)) -> f64| {
+                mean(&sessions.iter().map(f).collect::<Vec<_>>()).unwrap_or(0.0)
+            };
+            ModelTokenProfile {
+                model,
+                mean_input_fraction: column(|s| s.0),
+                mean_output_fraction: column(|s| s.1),
+                mean_total_tokens_per_session: column(|s| s.2),
+                session_count: sessions.len() as i64,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptionIndex {
+    pause_count: i64,
+    total_pause_ms: i64,
+    // total_pause_ms / session duration
+    pause_fraction: f64,
+    mean_pause_ms: f64,
+}
+
+// Pauses are sample gaps longer than `pause_threshold_ms` within the session.
+#[tauri::command]
+fn get_session_interruption_index(
+    session_key: String,
+    pause_threshold_ms: i64,
+    db_path: Option<String>,
+) -> Result<InterruptionIndex, String> {
+    if pause_threshold_ms <= 0 {
+        return Err("pause_threshold_ms must be positive".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let pauses: Vec<i64> = samples
+        .windows(2)
+        .map(|w| w[1].ts_ms - w[0].ts_ms)
+        .filter(|&gap| gap > pause_threshold_ms)
+        .collect();
+    let total_pause_ms: i64 = pauses.iter().sum();
+    let duration_ms = match (samples.first(), samples.last()) {
+        (Some(f), Some(l)) => l.ts_ms - f.ts_ms,
+        _ => 0,
+    };
+    Ok(InterruptionIndex {
+        pause_count: pauses.len() as i64,
+        total_pause_ms,
+        pause_fraction: if duration_ms > 0 {
+            total_pause_ms as f64 / duration_ms as f64
+        } else {
+            0.0
+        },
+        mean_pause_ms: mean(&pauses.iter().map(|&p| p as f64).collect::<Vec<_>>()).unwrap_or(0.0),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrowthStep {
+    ts_ms: i64,
+    // 1-based
+    step_number: i64,
+    // total_tokens gained since the previous sample; context usage tracks total_tokens
+    delta_context_tokens: Option<i64>,
+    cumulative_pct_used: Option<f64>,
+    // mean delta over steps 1..=step_number
+    tokens_per_step: Option<f64>,
+}
+
+// One step per sample whose total_tokens grew over the previous sample, i.e. per completed call.
+#[tauri::command]
+fn get_context_growth_profile(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<GrowthStep>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let mut out: Vec<GrowthStep> = Vec::new();
+    let mut grown = 0i64;
+    for w in samples.windows(2) {
+        let Some(delta) = counter_delta(w[0].total_tokens, w[1].total_tokens).filter(|&d| d > 0)
+        else {
+            continue;
+        };
+        grown += delta;
+        let step_number = out.len() as i64 + 1;
+        out.push(GrowthStep {
+            ts_ms: w[1].ts_ms,
+            step_number,
+            delta_context_tokens: Some(delta),
+            cumulative_pct_used: w[1].percent_used.map(|p| p as f64),
+            tokens_per_step: Some(grown as f64 / step_number as f64),
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalVelocity {
+    combined_tokens_per_s: f64,
+    active_session_count: i64,
+    dominant_session_key: Option<String>,
+    // dominant session's share of the combined rate; 0 when nothing is generating
+    dominant_session_pct: f64,
+}
+
+// Sum of each active session's latest pair rate; a session is active with a sample in the last
+// PULSE_WINDOW_MS and contributes 0 until it has two samples there.
+#[tauri::command]
+fn get_global_token_velocity(db_path: Option<String>) -> Result<GlobalVelocity, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let end = now_ms();
+    let sessions = load_samples_by_session_in_range(&conn, None, end - PULSE_WINDOW_MS, end)?;
+    let rates: Vec<(Option<String>, f64)> = sessions
+        .iter()
+        .map(|s| {
+            let rate = match s.as_slice() {
+                [.., a, b] => pair_rate(a, b).unwrap_or(0.0),
+                _ => 0.0,
+            };
+            (s[0].session_key.clone(), rate)
+        })
+        .collect();
+    let combined_tokens_per_s: f64 = rates.iter().map(|r| r.1).fold(0.0, |a, b| a + b);
+    let dominant = rates
+        .iter()
+        .filter(|r| r.1 > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(GlobalVelocity {
+        combined_tokens_per_s,
+        active_session_count: sessions.len() as i64,
+        dominant_session_key: dominant.and_then(|d| d.0.clone()),
+        dominant_session_pct: dominant.map_or(0.0, |d| d.1 / combined_tokens_per_s * 100.0),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionCostPerMinute {
+    session_key: String,
+    model: Option<String>,
+    total_cost_usd: f64,
+    active_minutes: f64,
+    cost_per_minute_usd: f64,
+}
+
+// Session cost over active time, counted as in get_average_tokens_per_active_hour. Sessions without
+// a price or without active time are skipped; most expensive per minute first.
+#[tauri::command]
+fn get_cost_per_session_minute(
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<SessionCostPerMinute>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        let (f, l) = (&session[0], &session[session.len() - 1]);
+        let Some(session_key) = l.session_key.clone() else {
+            continue;
+        };
+        let active_ms: i64 = session
+            .windows(2)
+            .map(|w| w[1].ts_ms - w[0].ts_ms)
+            .filter(|&gap| gap <= ACTIVE_HOUR_MAX_GAP_MS)
+            .sum();
+        let Some(total_cost_usd) = cost_config.cost_usd(
+            l.model.as_deref(),
+            counter_delta(f.input_tokens, l.input_tokens).unwrap_or(0),
+            counter_delta(f.output_tokens, l.output_tokens).unwrap_or(0),
+        ) else {
+            continue;
+        };
+        if active_ms == 0 {
+            continue;
+        }
+        let active_minutes = active_ms as f64 / 60_000.0;
+        out.push(SessionCostPerMinute {
+            session_key,
+            model: l.model.clone(),
+            total_cost_usd,
+            active_minutes,
+            cost_per_minute_usd: total_cost_usd / active_minutes,
+        });
+    }
+    out.sort_by(|a, b| b.cost_per_minute_usd.total_cmp(&a.cost_per_minute_usd));
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAdjustment {
+    ts_ms: i64,
+    session_key: String,
+    old_budget: i64,
+    new_budget: i64,
+}
+
+#[tauri::command]
+fn record_budget_adjustment(
+    ts_ms: i64,
+    session_key: String,
+    old_budget: i64,
+    new_budget: i64,
+    db_path: Option<String>,
+) -> Result<(), String> {
+    if session_key.trim().is_empty() {
+        return Err("session_key must not be empty".to_string());
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_db(&db_path)?;
+
+    conn.execute(
+        r#"
+        INSERT INTO budget_adjustment (ts_ms, session_key, old_budget, new_budget)
+        VALUES (?1, ?2, ?3, ?4)
+        "#,
+        rusqlite::params![ts_ms, session_key, old_budget, new_budget],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+fn get_session_budget_history(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<BudgetAdjustment>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    load_budget_adjustments(&conn, &session_key)
+}
+
+fn load_budget_adjustments(
+    conn: &Connection,
+    session_key: &str,
+) -> Result<Vec<BudgetAdjustment>, String> {
+    if !table_exists(conn, "budget_adjustment")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT ts_ms, session_key, old_budget, new_budget
+            FROM budget_adjustment
+            WHERE session_key = ?1
+            ORDER BY ts_ms ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([session_key], |r| {
+            Ok(BudgetAdjustment {
+                ts_ms: r.get(0)?,
+                session_key: r.get(1)?,
+                old_budget: r.get(2)?,
+                new_budget: r.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCostBreakdown {
+    tag: String,
+    input_tokens: i64,
+    output_tokens: i64,
+    cost_usd: f64,
+    session_count: i64,
+    avg_cost_per_session_usd: f64,
+}
+
+// Like get_cost_by_project, restricted to `tags` (every tag when empty) and without the untagged
+// bucket. Each requested tag gets a row, even without sessions.
+#[tauri::command]
+fn get_cost_breakdown_by_tag(
+    tags: Vec<String>,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<TagCostBreakdown>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let session_tags = load_session_tags(&conn)?;
+    let wanted: std::collections::BTreeSet<String> = if tags.is_empty() {
+        session_tags.values().flatten().cloned().collect()
+    } else {
+        tags.into_iter().collect()
+    };
+    let mut by_tag: std::collections::BTreeMap<String, TagCostBreakdown> = wanted
+        .into_iter()
+        .map(|tag| {
+            let row = TagCostBreakdown {
+                tag: tag.clone(),
+                input_tokens: 0,
+                output_tokens: 0,
+                cost_usd: 0.0,
+                session_count: 0,
+                avg_cost_per_session_usd: 0.0,
+            };
+            (tag, row)
+        })
+        .collect();
+    for s in load_session_totals(&conn)? {
+        let Some(tags) = session_tags.get(&s.session_key) else {
+            continue;
+        };
+        let input = s.input_tokens.unwrap_or(0);
+        let output = s.output_tokens.unwrap_or(0);
+        // Unpriced models still count towards token totals, just not cost.
+        let cost = cost_config
+            .cost_usd(s.model.as_deref(), input, output)
+            .unwrap_or(0.0);
+        for tag in tags {
+            if let Some(t) = by_tag.get_mut(tag) {
+                t.input_tokens += input;
+                t.output_tokens += output;
+                t.cost_usd += cost;
+                t.session_count += 1;
+            }
+        }
+    }
+
+    let mut out: Vec<TagCostBreakdown> = by_tag
+        .into_values()
+        .map(|mut t| {
+            if t.session_count > 0 {
+                t.avg_cost_per_session_usd = t.cost_usd / t.session_count as f64;
+            }
+            t
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+    Ok(out)
+}
+
+// Estimated session cost at `percentile`, interpolated between neighbouring sessions; `None`
+// without priced sessions.
+#[tauri::command]
+fn get_percentile_session_cost(
+    percentile: f64,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    if !(0.0..=100.0).contains(&percentile) {
+        return Err(format!(
+            "percentile must be in [0, 100], got {}",
+            percentile
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut costs: Vec<f64> = load_session_totals(&conn)?
+        .iter()
+        .filter_map(|t| {
+            cost_config.cost_usd(
+                t.model.as_deref(),
+                t.input_tokens.unwrap_or(0),
+                t.output_tokens.unwrap_or(0),
+            )
+        })
+        .collect();
+    costs.sort_by(f64::total_cmp);
+    Ok(self::percentile(&costs, percentile))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveMetricsWithDelta {
+    live: LiveMetrics,
+    // from the first sample of the live session to its latest one
+    delta_input_tokens: Option<i64>,
+    delta_output_tokens: Option<i64>,
+    delta_total_tokens: Option<i64>,
+    delta_net_rx_bytes: Option<i64>,
+    delta_net_tx_bytes: Option<i64>,
+}
+
+#[tauri::command]
+fn get_live_metrics_with_session_delta(
+    db_path: Option<String>,
+) -> Result<LiveMetricsWithDelta, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let live = live_metrics(&conn)?;
+    let ends = match &live.session_key {
+        Some(key) => {
+            let end = |order: &str| {
+                let sql = format!(
+                    "SELECT {} FROM samples WHERE session_key = ?1 ORDER BY ts_ms {} LIMIT 1",
+                    SAMPLE_COLUMNS, order
+                );
+                timed_query(&conn, &sql, [key], sample_from_row)
+            };
+            Some((end("ASC")?, end("DESC")?))
+        }
+        None => None,
+    };
+    let delta = |field: fn(&SampleRow) -> Option<i64>| {
+        ends.as_ref()
+            .and_then(|(f, l)| counter_delta(field(f), field(l)))
+    };
+    Ok(LiveMetricsWithDelta {
+        delta_input_tokens: delta(|s| s.input_tokens),
+        delta_output_tokens: delta(|s| s.output_tokens),
+        delta_total_tokens: delta(|s| s.total_tokens),
+        delta_net_rx_bytes: delta(|s| s.net_rx_bytes),
+        delta_net_tx_bytes: delta(|s| s.net_tx_bytes),
+        live,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSpeedBenchmark {
+    model: String,
+    p50_tokens_per_s: f64,
+    p95_tokens_per_s: f64,
+    // adjacent-pair rates measured
+    sample_rate_observations: i64,
+}
+
+// Pair-rate percentiles per model, skipping models seen in fewer than `min_sessions` sessions.
+// As in get_model_latency_profile, a pair belongs to the later sample's model.
+#[tauri::command]
+fn get_tokens_per_s_p50_by_model(
+    min_sessions: u32,
+    db_path: Option<String>,
+) -> Result<Vec<ModelSpeedBenchmark>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    // model -> (rates, sessions)
+    let mut by_model: std::collections::BTreeMap<String, (Vec<f64>, i64)> =
+        std::collections::BTreeMap::new();
+    for session in load_samples_by_session(&conn)? {
+        let mut seen: std::collections::BTreeSet<&str> = std::collections::BTreeSet::new();
+        for w in session.windows(2) {
+            let (Some(m), Some(rate)) = (w[1].model.as_deref(), pair_rate(&w[0], &w[1])) else {
+                continue;
+            };
+            let entry = by_model.entry(m.to_string()).or_default();
+            entry.0.push(rate);
+            if seen.insert(m) {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    Ok(by_model
+        .into_iter()
+        .filter(|(_, (_, sessions))| *sessions >= min_sessions as i64)
+        .map(|(model, (mut rates, _))| {
+            rates.sort_by(f64::total_cmp);
+            ModelSpeedBenchmark {
+                model,
+                p50_tokens_per_s: percentile(&rates, 50.0).unwrap_or(0.0),
+                p95_tokens_per_s: percentile(&rates, 95.0).unwrap_or(0.0),
+                sample_rate_observations: rates.len() as i64,
+            }
+        })
+        .collect())
+}
+
+// Running cost (as in get_session_cost_over_time) at the last sample at or before `ts_ms`;
+// `None` before the session's first sample.
+#[tauri::command]
+fn get_session_cost_at_time(
+    session_key: String,
+    ts_ms: i64,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Option<f64>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let upto = samples.partition_point(|s| s.ts_ms <= ts_ms);
+    if upto == 0 {
+        return Ok(None);
+    }
+    Ok(Some(
+        cost_timeline(&samples[..upto], &cost_config)
+            .last()
+            .map_or(0.0, |p| p.cumulative_cost_usd),
+    ))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaturationNetworkStats {
+    session_key: String,
+    // first crossing of threshold_pct; the fields below are session deltas up to it
+    saturation_ts_ms: Option<i64>,
+    net_rx_bytes_at_saturation: Option<i64>,
+    net_tx_bytes_at_saturation: Option<i64>,
+    // first-to-last delta over the whole session
+    total_rx_bytes: Option<i64>,
+    rx_pct_spent_before_saturation: Option<f64>,
+}
+
+// Network bytes a session used up to its first context saturation crossing (as in
+// get_context_saturation_events), against its total. Unsaturated sessions carry `None`s.
+#[tauri::command]
+fn get_net_bytes_at_saturation(
+    threshold_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<SaturationNetworkStats>, String> {
+    if !(1..=100).contains(&threshold_pct) {
+        return Err(format!(
+            "threshold_pct must be between 1 and 100, got {}",
+            threshold_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    for session in load_samples_by_session(&conn)? {
+        let (f, l) = (&session[0], &session[session.len() - 1]);
+        let Some(session_key) = f.session_key.clone() else {
+            continue;
+        };
+        let saturation_ts_ms = saturation_crossings(&session, threshold_pct)
+            .first()
+            .map(|e| e.ts_ms);
+        let at = saturation_ts_ms.and_then(|ts| session.iter().find(|s| s.ts_ms == ts));
+        let net_rx_bytes_at_saturation =
+            at.and_then(|s| counter_delta(f.net_rx_bytes, s.net_rx_bytes));
+        let total_rx_bytes = counter_delta(f.net_rx_bytes, l.net_rx_bytes);
+        out.push(SaturationNetworkStats {
+            session_key,
+            saturation_ts_ms,
+            net_rx_bytes_at_saturation,
+            net_tx_bytes_at_saturation: at
+                .and_then(|s| counter_delta(f.net_tx_bytes, s.net_tx_bytes)),
+            total_rx_bytes,
+            rx_pct_spent_before_saturation: net_rx_bytes_at_saturation
+                .zip(total_rx_bytes.filter(|&t| t > 0))
+                .map(|(a, t)| a as f64 / t as f64 * 100.0),
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionWithStats {
+    session_key: String,
+    // model of the most recent sample
+    model: Option<String>,
+    first_ts_ms: i64,
+    last_ts_ms: i64,
+    sample_count: i64,
+    // first-to-last delta
+    total_tokens: Option<i64>,
+    duration_ms: i64,
+    // mean adjacent-pair rate, skipping counter resets
+    mean_tokens_per_s: Option<f64>,
+    peak_percent_used: Option<i64>,
+    // gaps over DEFAULT_REACTIVATION_IDLE_MS, as in get_session_reactivation_count
+    reactivation_count: i64,
+}
+
+// Every session with its summary stats from one query, most recently active first.
+#[tauri::command]
+fn get_session_list_with_stats(db_path: Option<String>) -> Result<Vec<SessionWithStats>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH ordered AS (
+                SELECT session_key, ts_ms, model, total_tokens, percent_used,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms ASC) AS rn_first,
+                       ROW_NUMBER() OVER (PARTITION BY session_key ORDER BY ts_ms DESC) AS rn_last,
+                       ts_ms - LAG(ts_ms) OVER (PARTITION BY session_key ORDER BY ts_ms) AS gap_ms,
+                       total_tokens - LAG(total_tokens) OVER (PARTITION BY session_key ORDER BY ts_ms)
+                           AS delta
+                FROM samples
+                WHERE session_key IS NOT NULL
+            ),
+            stats AS (
+                SELECT session_key, COUNT(*) AS sample_count,
+                       MIN(ts_ms) AS first_ts, MAX(ts_ms) AS last_ts,
+                       MAX(percent_used) AS peak_pct,
+                       AVG(CASE WHEN delta >= 0 AND gap_ms > 0 THEN delta * 1000.0 / gap_ms END)
+                           AS mean_rate,
+                       COUNT(CASE WHEN gap_ms > ?1 THEN 1 END) AS reactivations
+                FROM ordered
+                GROUP BY session_key
+            )
+            SELECT s.session_key, l.model, s.first_ts, s.last_ts, s.sample_count,
+                   CASE WHEN l.total_tokens >= f.total_tokens
+                        THEN l.total_tokens - f.total_tokens END,
+                   s.mean_rate, s.peak_pct, s.reactivations
+            FROM stats s
+            JOIN ordered f ON f.session_key = s.session_key AND f.rn_first = 1
+            JOIN ordered l ON l.session_key = s.session_key AND l.rn_last = 1
+            ORDER BY s.last_ts DESC, s.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([DEFAULT_REACTIVATION_IDLE_MS], |r| {
+            let first_ts_ms: i64 = r.get(2)?;
+            let last_ts_ms: i64 = r.get(3)?;
+            Ok(SessionWithStats {
+                session_key: r.get(0)?,
+                model: r.get(1)?,
+                first_ts_ms,
+                last_ts_ms,
+                sample_count: r.get(4)?,
+                total_tokens: r.get(5)?,
+                duration_ms: last_ts_ms - first_ts_ms,
+                mean_tokens_per_s: r.get(6)?,
+                peak_percent_used: r.get(7)?,
+                reactivation_count: r.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const COST_MOVING_AVERAGE_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmoothCostPoint {
+    date_label: String,
+    raw_cost_usd: f64,
+    // mean over this day and the preceding window_days - 1 days
+    moving_avg_cost_usd: f64,
+}
+
+// Daily cost (local days, today last) for the last COST_MOVING_AVERAGE_DAYS days, each with its
+// trailing `window_days` average. History reaches back max(window_days * 3, 30) days; the window
+// is shorter where that runs out.
+#[tauri::command]
+fn get_cost_moving_average(
+    window_days: u32,
+    tz_offset_minutes: i32,
+    cost_config: CostConfig,
+    db_path: Option<String>,
+) -> Result<Vec<SmoothCostPoint>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let window = window_days.clamp(1, 14) as usize;
+    let history_days = (window as i64 * 3).max(COST_MOVING_AVERAGE_DAYS);
+    let today = local_day_start_ms(now_ms(), tz_offset_minutes);
+    let mut days = Vec::new();
+    for i in (0..history_days).rev() {
+        let day = today - i * DAY_MS;
+        let (_, cost) = daily_usage(&conn, &cost_config, day)?;
+        days.push((day, cost));
+    }
+
+    let skip = days.len() - COST_MOVING_AVERAGE_DAYS as usize;
+    Ok((skip..days.len())
+        .map(|i| {
+            let trailing: Vec<f64> = days[(i + 1).saturating_sub(window)..=i]
+                .iter()
+                .map(|d| d.1)
+                .collect();
+            SmoothCostPoint {
+                date_label: local_date_label(days[i].0, tz_offset_minutes),
+                raw_cost_usd: days[i].1,
+                moving_avg_cost_usd: mean(&trailing).unwrap_or(0.0),
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateTrend {
+    slope_tokens_per_s_per_s: f64,
+    r_squared: f64,
+    // latest pair rate
+    current_rate: f64,
+    trend: TrendDirection,
+}
+
+// Linear fit over the session's last `lookback_samples` pair rates against seconds since the
+// first of them: Rising means the rate is accelerating. `None` with fewer than two rates.
+#[tauri::command]
+fn get_realtime_rate_trend(
+    session_key: String,
+    lookback_samples: u32,
+    db_path: Option<String>,
+) -> Result<Option<RateTrend>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let rates = pair_rates(&load_session_samples(&conn, &session_key)?);
+    let recent = &rates[rates
+        .len()
+        .saturating_sub(lookback_samples.clamp(3, 20) as usize)..];
+    let (Some(&(t0, _)), Some(&(_, current_rate))) = (recent.first(), recent.last()) else {
+        return Ok(None);
+    };
+    let xy: Vec<(f64, f64)> = recent
+        .iter()
+        .map(|&(ts, r)| ((ts - t0) as f64 / 1000.0, r))
+        .collect();
+    Ok(
+        linear_regression(&xy).map(|(slope, _, r_squared)| RateTrend {
+            slope_tokens_per_s_per_s: slope,
+            r_squared,
+            current_rate,
+            trend: trend_direction(slope, r_squared),
+        }),
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllRollupsSummary {
+    r_1h: Rollup,
+    r_6h: Rollup,
+    r_24h: Rollup,
+    r_3d: Rollup,
+    r_7d: Rollup,
+    r_30d: Rollup,
+}
+
+// The standard windows ending now, read in one transaction. Unlike `labeled_rollup`, a window
+// without samples reports zero deltas instead of `None`, and any other failure is returned.
+#[tauri::command]
+fn get_all_rollups_summary(db_path: Option<String>) -> Result<AllRollupsSummary, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let mut conn = open_readonly(&db_path)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let end = now_ms();
+    let rollup = |label: &str, duration_ms: i64| -> Result<Rollup, String> {
+        let start = end - duration_ms;
+        let has_samples = timed_query(
+            &tx,
+            "SELECT EXISTS(SELECT 1 FROM samples WHERE ts_ms >= ?1 AND ts_ms <= ?2)",
+            [start, end],
+            |r| r.get::<_, bool>(0),
+        )?;
+        if !has_samples {
+            return Ok(Rollup {
+                window_label: label.to_string(),
+                start_ts_ms: start,
+                end_ts_ms: end,
+                input_tokens: Some(0),
+                output_tokens: Some(0),
+                total_tokens: Some(0),
+                net_rx_bytes: Some(0),
+                net_tx_bytes: Some(0),
+                estimated_cost_usd: None,
+                source_count: 0,
+            });
+        }
+        let mut r = get_window_delta(&tx, start, end)?;
+        r.window_label = label.to_string();
+        Ok(r)
+    };
+    Ok(AllRollupsSummary {
+        r_1h: rollup("1h", HOUR_MS)?,
+        r_6h: rollup("6h", 6 * HOUR_MS)?,
+        r_24h: rollup("24h", DAY_MS)?,
+        r_3d: rollup("3d", 3 * DAY_MS)?,
+        r_7d: rollup("7d", 7 * DAY_MS)?,
+        r_30d: rollup("30d", 30 * DAY_MS)?,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplexityScore {
+    // 0..1, see the `complexity` module for the formula
+    score: f64,
+    components: ComplexityComponents,
+}
+
+// `None` for a session without samples; `weights` defaults to equal weighting.
+#[tauri::command]
+fn get_session_complexity_score(
+    session_key: String,
+    weights: Option<ComplexityWeights>,
+    db_path: Option<String>,
+) -> Result<Option<ComplexityScore>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let (Some(f), Some(l)) = (samples.first(), samples.last()) else {
+        return Ok(None);
+    };
+    let model_switches = samples
+        .windows(2)
+        .filter(|w| match (&w[0].model, &w[1].model) {
+            (Some(a), Some(b)) => a != b,
+            _ => false,
+        })
+        .count() as i64;
+    let rates: Vec<f64> = pair_rates(&samples).into_iter().map(|(_, r)| r).collect();
+    // Same burst rule as get_complete_session_profile.
+    let mut burst_count = 0;
+    if let Some(m) = mean(&rates).filter(|m| *m > 0.0) {
+        let mut prev_bursting = false;
+        for w in samples.windows(2) {
+            let bursting = pair_rate(&w[0], &w[1]).is_some_and(|r| r > 2.0 * m);
+            if bursting && !prev_bursting {
+                burst_count += 1;
+            }
+            prev_bursting = bursting;
+        }
+    }
+    let requests: Vec<f64> = samples
+        .windows(2)
+        .filter_map(|w| counter_delta(w[0].total_tokens, w[1].total_tokens))
+        .filter(|&d| d > 0)
+        .map(|d| d as f64)
+        .collect();
+
+    let components = ComplexityComponents {
+        context_utilization_pct: samples
+            .iter()
+            .filter_map(|s| s.percent_used)
+            .max()
+            .unwrap_or(0) as f64,
+        session_duration_hours: (l.ts_ms - f.ts_ms) as f64 / HOUR_MS as f64,
+        model_switches,
+        burst_count,
+        mean_tokens_per_request: mean(&requests).unwrap_or(0.0),
+    };
+    let score = complexity::score(&components, &weights.unwrap_or_default())?;
+    Ok(Some(ComplexityScore { score, components }))
+}
+
+// A sample is a rate change point when its incoming and outgoing pair rates differ by more than
+// this fraction of the larger one.
+const TIMELINE_RATE_CHANGE_FRACTION: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimelineEntryType {
+    Sample,
+    Annotation,
+    BudgetAdjustment,
+    ModelSwitch,
+    Saturation,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimelineData {
+    Sample(SampleRow),
+    Annotation(Annotation),
+    BudgetAdjustment(BudgetAdjustment),
+    ModelSwitch { from: String, to: String },
+    Saturation(SaturationEvent),
+    // e.g. a token counter that went backwards
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEntry {
+    ts_ms: i64,
+    entry_type: TimelineEntryType,
+    data: TimelineData,
+}
+
+fn is_rate_change_point(prev: &SampleRow, cur: &SampleRow, next: &SampleRow) -> bool {
+    match (pair_rate(prev, cur), pair_rate(cur, next)) {
+        (Some(a), Some(b)) => {
+            let larger = a.abs().max(b.abs());
+            larger > 0.0 && (a - b).abs() > TIMELINE_RATE_CHANGE_FRACTION * larger
+        }
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+// Samples, annotations, budget adjustments, model switches, SATURATION_PCT crossings and counter
+// regressions for one session, oldest first (samples before events at the same ts_ms). Without
+// `include_all_samples` only the first, the last and rate change points are kept.
+#[tauri::command]
+fn get_session_timeline_events(
+    session_key: String,
+    include_all_samples: bool,
+    db_path: Option<String>,
+) -> Result<Vec<TimelineEntry>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    let mut out = Vec::new();
+    for (i, s) in samples.iter().enumerate() {
+        let keep = include_all_samples
+            || i == 0
+            || i + 1 == samples.len()
+            || is_rate_change_point(&samples[i - 1], s, &samples[i + 1]);
+        if keep {
+            out.push(TimelineEntry {
+                ts_ms: s.ts_ms,
+                entry_type: TimelineEntryType::Sample,
+                data: TimelineData::Sample(s.clone()),
+            });
+        }
+    }
+    for w in samples.windows(2) {
+        if let (Some(from), Some(to)) = (&w[0].model, &w[1].model) {
+            if from != to {
+                out.push(TimelineEntry {
+                    ts_ms: w[1].ts_ms,
+                    entry_type: TimelineEntryType::ModelSwitch,
+                    data: TimelineData::ModelSwitch {
+                        from: from.clone(),
+                        to: to.clone(),
+                    },
+                });
+            }
+        }
+        if let (Some(a), Some(b)) = (w[0].total_tokens, w[1].total_tokens) {
+            if b < a {
+                out.push(TimelineEntry {
+                    ts_ms: w[1].ts_ms,
+                    entry_type: TimelineEntryType::Error,
+                    data: TimelineData::Error {
+                        message: format!("total_tokens went backwards ({} -> {})", a, b),
+                    },
+                });
+            }
+        }
+    }
+    for e in saturation_crossings(&samples, SATURATION_PCT) {
+        out.push(TimelineEntry {
+            ts_ms: e.ts_ms,
+            entry_type: TimelineEntryType::Saturation,
+            data: TimelineData::Saturation(e),
+        });
+    }
+    for a in load_annotations(&conn, &session_key)? {
+        out.push(TimelineEntry {
+            ts_ms: a.ts_ms,
+            entry_type: TimelineEntryType::Annotation,
+            data: TimelineData::Annotation(a),
+        });
+    }
+    for b in load_budget_adjustments(&conn, &session_key)? {
+        out.push(TimelineEntry {
+            ts_ms: b.ts_ms,
+            entry_type: TimelineEntryType::BudgetAdjustment,
+            data: TimelineData::BudgetAdjustment(b),
+        });
+    }
+    out.sort_by_key(|e| e.ts_ms);
+
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtilizationVelocity {
+    // later sample of the pair
+    ts_ms: i64,
+    // negative after summarization; above ~5 is alarming. None without percent_used or when dt <= 0
+    pct_per_minute: Option<f64>,
+}
+
+// One point per adjacent sample pair of the session.
+#[tauri::command]
+fn get_context_utilization_velocity(
+    session_key: String,
+    db_path: Option<String>,
+) -> Result<Vec<UtilizationVelocity>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let samples = load_session_samples(&conn, &session_key)?;
+    Ok(samples
+        .windows(2)
+        .map(|w| {
+            let dt_ms = w[1].ts_ms - w[0].ts_ms;
+            let pct_per_minute = match (w[0].percent_used, w[1].percent_used) {
+                (Some(a), Some(b)) if dt_ms > 0 => Some((b - a) as f64 * 60_000.0 / dt_ms as f64),
+                _ => None,
+            };
+            UtilizationVelocity {
+                ts_ms: w[1].ts_ms,
+                pct_per_minute,
+            }
+        })
+        .collect())
+}
+
+const MAX_EFFICIENCY_RANK_SESSIONS: u32 = 100;
+// Below this the per-1k figure is dominated by input cost and says little.
+const MIN_EFFICIENCY_OUTPUT_TOKENS: i64 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEfficiencyRank {
+    // 1-based
+    rank: u32,
+    session_key: String,
+    model: Option<String>,
+    output_tokens: i64,
+    cost_usd: f64,
+    cost_per_1k_output: f64,
+}
+
+// Cheapest sessions per output token first; unpriced sessions are skipped.
+#[tauri::command]
+fn get_session_cost_efficiency_rank(
+    cost_config: CostConfig,
+    top_n: u32,
+    db_path: Option<String>,
+) -> Result<Vec<CostEfficiencyRank>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut ranked: Vec<(SessionTotals, i64, f64)> = load_session_totals(&conn)?
+        .into_iter()
+        .filter_map(|t| {
+            let output_tokens = t
+                .output_tokens
+                .filter(|&o| o >= MIN_EFFICIENCY_OUTPUT_TOKENS)?;
+            let cost = cost_config.cost_usd(
+                t.model.as_deref(),
+                t.input_tokens.unwrap_or(0),
+                output_tokens,
+            )?;
+            Some((t, output_tokens, cost))
+        })
+        .collect();
+    ranked.sort_by(|a, b| (a.2 / a.1 as f64).total_cmp(&(b.2 / b.1 as f64)));
+    ranked.truncate(top_n.min(MAX_EFFICIENCY_RANK_SESSIONS) as usize);
+
+    Ok(ranked
+        .into_iter()
+        .enumerate()
+        .map(|(i, (t, output_tokens, cost_usd))| CostEfficiencyRank {
+            rank: i as u32 + 1,
+            session_key: t.session_key,
+            model: t.model,
+            output_tokens,
+            cost_usd,
+            cost_per_1k_output: cost_usd / output_tokens as f64 * 1000.0,
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokensToSaturation {
+    current_pct: i64,
+    target_pct: i64,
+    // (target_pct - current_pct)% of context_tokens; ignores how the next calls grow
+    tokens_remaining_estimate: Option<i64>,
+    context_tokens: i64,
+}
+
+// From the session's latest sample. `None` without samples, without percent_used/context_tokens
+// there, or once the session is already at `target_pct`.
+#[tauri::command]
+fn get_tokens_to_context_saturation(
+    session_key: String,
+    target_pct: i64,
+    db_path: Option<String>,
+) -> Result<Option<TokensToSaturation>, String> {
+    if !(1..=100).contains(&target_pct) {
+        return Err(format!(
+            "target_pct must be between 1 and 100, got {}",
+            target_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT percent_used, context_tokens
+            FROM samples
+            WHERE session_key = ?1
+            ORDER BY ts_ms DESC
+            LIMIT 1
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query_map([&session_key], |r| {
+            Ok((r.get::<_, Option<i64>>(0)?, r.get::<_, Option<i64>>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    let Some((Some(current_pct), Some(context_tokens))) =
+        rows.next().transpose().map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    if current_pct >= target_pct {
+        return Ok(None);
+    }
+
+    Ok(Some(TokensToSaturation {
+        current_pct,
+        target_pct,
+        tokens_remaining_estimate: Some(
+            ((target_pct - current_pct) as f64 / 100.0 * context_tokens as f64) as i64,
+        ),
+        context_tokens,
+    }))
+}
+
+// The session's samples with percent_used within `tolerance_pct` of `target_pct`, oldest first.
+#[tauri::command]
+fn get_session_metrics_at_context_pct(
+    session_key: String,
+    target_pct: i64,
+    tolerance_pct: i64,
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    if !(0..=100).contains(&target_pct) {
+        return Err(format!(
+            "target_pct must be between 0 and 100, got {}",
+            target_pct
+        ));
+    }
+    if tolerance_pct < 0 {
+        return Err(format!(
+            "tolerance_pct must be non-negative, got {}",
+            tolerance_pct
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let sql = format!(
+        "SELECT {} FROM samples \
+         WHERE session_key = ?1 AND ABS(percent_used - ?2) <= ?3 \
+         ORDER BY ts_ms ASC",
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![session_key, target_pct, tolerance_pct],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+const DB_HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+// Hex SHA-256 of the main database file only; writes still sitting in the -wal file don't count
+// until they are checkpointed.
+fn file_sha256_hex(path: &str) -> Result<String, String> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = sha256::Sha256::default();
+    let mut chunk = vec![0u8; DB_HASH_CHUNK_BYTES];
+    loop {
+        let n = file.read(&mut chunk).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&chunk[..n]);
+    }
+    Ok(hasher.finish_hex())
+}
+
+// Also stores the hash as the `last_db_hash` setting, the baseline health_check compares against.
+// Only this command moves the baseline. The setting write is left in the -wal file (no checkpoint)
+// so it doesn't itself change the hashed file.
+#[tauri::command]
+fn get_database_file_hash(db_path: Option<String>) -> Result<String, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    // Schema first, through a normal connection, so its checkpoint lands before the hash.
+    drop(open_db(&db_path)?);
+    let hash = file_sha256_hex(&db_path)?;
+    let conn = open_no_checkpoint(&db_path)?;
+    set_setting(&conn, "last_db_hash", &hash)?;
+    Ok(hash)
+}
+
+const DEFAULT_SAMPLES_LIMIT: u32 = 500;
+
+// Raw rows in `[start_ms, end_ms]`, oldest first, for charting.
+#[tauri::command]
+fn get_samples(
+    start_ms: i64,
+    end_ms: i64,
+    limit: Option<u32>,
+    db_path: Option<String>,
+) -> Result<Vec<SampleRow>, String> {
+    if start_ms > end_ms {
+        return Err(format!("start_ms {} is after end_ms {}", start_ms, end_ms));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM samples
+        WHERE ts_ms BETWEEN ?1 AND ?2
+        ORDER BY ts_ms ASC
+        LIMIT ?3
+        "#,
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![start_ms, end_ms, limit.unwrap_or(DEFAULT_SAMPLES_LIMIT)],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    session_key: String,
+    // model of the most recent sample
+    model: Option<String>,
+    first_ts_ms: i64,
+    last_ts_ms: i64,
+    duration_ms: i64,
+    // first-to-last over the whole session; None when the counter reset
+    input_tokens_delta: Option<i64>,
+    output_tokens_delta: Option<i64>,
+    total_tokens_delta: Option<i64>,
+}
+
+// Sessions with a sample at or after `since_ms` (all when None), oldest first.
+#[tauri::command]
+fn get_sessions(
+    since_ms: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<SessionSummary>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    Ok(load_session_totals(&conn)?
+        .into_iter()
+        .filter(|t| since_ms.is_none_or(|since| t.last_ts_ms >= since))
+        .map(|t| SessionSummary {
+            duration_ms: t.last_ts_ms - t.first_ts_ms,
+            session_key: t.session_key,
+            model: t.model,
+            first_ts_ms: t.first_ts_ms,
+            last_ts_ms: t.last_ts_ms,
+            input_tokens_delta: t.input_tokens,
+            output_tokens_delta: t.output_tokens,
+            total_tokens_delta: t.total_tokens,
+        })
+        .collect())
+}
+
+const MAX_HOURLY_BUCKET_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourlyBucket {
+    hour_start_ms: i64,
+    hour_end_ms: i64,
+    // `None` for hours without samples or across a counter reset
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+    total_tokens: Option<i64>,
+    net_rx_bytes: Option<i64>,
+    net_tx_bytes: Option<i64>,
+}
+
+// One bucket per hour from `start_ms`, oldest first; the last one is cut short at `end_ms`.
+// Each bucket is a `get_window_delta` over its own hour.
+#[tauri::command]
+fn get_hourly_buckets(
+    start_ms: i64,
+    end_ms: i64,
+    db_path: Option<String>,
+) -> Result<Vec<HourlyBucket>, String> {
+    if start_ms > end_ms {
+        return Err(format!("start_ms {} is after end_ms {}", start_ms, end_ms));
+    }
+    if end_ms - start_ms > MAX_HOURLY_BUCKET_DAYS * DAY_MS {
+        return Err(format!(
+            "hourly buckets span at most {} days",
+            MAX_HOURLY_BUCKET_DAYS
+        ));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let mut out = Vec::new();
+    let mut hour_start = start_ms;
+    while hour_start < end_ms {
+        let hour_end = (hour_start + HOUR_MS).min(end_ms);
+        let r = labeled_rollup(&conn, "", hour_start, hour_end);
+        out.push(HourlyBucket {
+            hour_start_ms: hour_start,
+            hour_end_ms: hour_end,
+            input_tokens: r.input_tokens,
+            output_tokens: r.output_tokens,
+            total_tokens: r.total_tokens,
+            net_rx_bytes: r.net_rx_bytes,
+            net_tx_bytes: r.net_tx_bytes,
+        });
+        hour_start = hour_end;
+    }
+    Ok(out)
+}
+
+// Quotes a CSV field only when it contains a delimiter, quote or line break.
+fn csv_field(value: Option<&str>) -> String {
+    match value {
+        Some(v) if v.contains([',', '"', '\n', '\r']) => format!("\"{}\"", v.replace('"', "\"\"")),
+        Some(v) => v.to_string(),
+        None => String::new(),
+    }
+}
+
+fn csv_number(value: Option<i64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+// Writes samples in [start_ms, end_ms] (either bound open when None) to `dest_path` as CSV, one row
+// per sample in `samples` column order, and returns the number of rows written. Refuses to replace
+// an existing file unless `overwrite` is true. Rows go to a temp file next to `dest_path` that is
+// renamed into place only once complete, so a failed export leaves nothing behind.
+#[tauri::command]
+fn export_samples(
+    dest_path: String,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+    overwrite: Option<bool>,
+    db_path: Option<String>,
+) -> Result<u64, String> {
+    if !overwrite.unwrap_or(false) && std::path::Path::new(&dest_path).exists() {
+        return Err(format!("{} already exists", dest_path));
+    }
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+
+    let tmp_path = format!("{}.tmp-{}", dest_path, std::process::id());
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .map_err(|e| format!("{}: {}", tmp_path, e))?;
+    let written = write_samples_csv(&conn, file, start_ms, end_ms).and_then(|n| {
+        std::fs::rename(&tmp_path, &dest_path)
+            .map(|_| n)
+            .map_err(|e| e.to_string())
+    });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+fn write_samples_csv(
+    conn: &Connection,
+    file: std::fs::File,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+) -> Result<u64, String> {
+    use std::io::Write;
+
+    let mut out = std::io::BufWriter::new(file);
+    let io_err = |e: std::io::Error| e.to_string();
+
+    writeln!(out, "{}", SAMPLE_COLUMNS.replace(", ", ",")).map_err(io_err)?;
+
+    let sql = format!(
+        "SELECT {} FROM samples WHERE ts_ms BETWEEN ?1 AND ?2 ORDER BY ts_ms ASC",
+        SAMPLE_COLUMNS
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(
+            [start_ms.unwrap_or(i64::MIN), end_ms.unwrap_or(i64::MAX)],
+            sample_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    let mut written = 0u64;
+    for row in rows {
+        let s = row.map_err(|e| e.to_string())?;
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            s.ts_ms,
+            csv_field(s.session_key.as_deref()),
+            csv_field(s.model.as_deref()),
+            csv_number(s.input_tokens),
+            csv_number(s.output_tokens),
+            csv_number(s.total_tokens),
+            csv_number(s.remaining_tokens),
+            csv_number(s.context_tokens),
+            csv_number(s.percent_used),
+            csv_number(s.net_rx_bytes),
+            csv_number(s.net_tx_bytes),
+        )
+        .map_err(io_err)?;
+        written += 1;
+    }
+    out.into_inner()
+        .map_err(|e| e.to_string())?
+        .sync_all()
+        .map_err(io_err)?;
+    Ok(written)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPeak {
+    session_key: String,
+    peak_tokens_per_s: Option<f64>,
+    peak_in_tokens_per_s: Option<f64>,
+    peak_out_tokens_per_s: Option<f64>,
+    // when `peak_tokens_per_s` was seen
+    peak_observed_ts_ms: Option<i64>,
+    // first-to-last sample span; 0 once the session's samples are gone
+    session_duration_ms: i64,
+}
+
+// Peaks recorded by `get_live_metrics`, highest `peak_tokens_per_s` first. With `since_ts_ms`,
+// only peaks observed at or after it.
+#[tauri::command]
+fn get_session_peaks(
+    since_ts_ms: Option<i64>,
+    db_path: Option<String>,
+) -> Result<Vec<SessionPeak>, String> {
+    let db_path = db_path.unwrap_or_else(db_path_default);
+    let conn = open_readonly(&db_path)?;
+    if !table_exists(&conn, "session_peaks")? {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT p.session_key, p.peak_tokens_per_s, p.peak_in_tokens_per_s,
+                   p.peak_out_tokens_per_s, p.peak_observed_ts_ms,
+                   COALESCE(s.duration_ms, 0)
+            FROM session_peaks p
+            LEFT JOIN (
+                SELECT session_key, MAX(ts_ms) - MIN(ts_ms) AS duration_ms
+                FROM samples
+                GROUP BY session_key
+            ) s ON s.session_key = p.session_key
+            WHERE ?1 IS NULL OR p.peak_observed_ts_ms >= ?1
+            ORDER BY p.peak_tokens_per_s DESC NULLS LAST, p.session_key ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([since_ts_ms], |r| {
+            Ok(SessionPeak {
+                session_key: r.get(0)?,
+                peak_tokens_per_s: r.get(1)?,
+                peak_in_tokens_per_s: r.get(2)?,
+                peak_out_tokens_per_s: r.get(3)?,
+                peak_observed_ts_ms: r.get(4)?,
+                session_duration_ms: r.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    log_sql::init();
+    // Read the config file now rather than on the first command.
+    AppConfig::get();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![get_live_metrics, get_rollups])
+        .manage(RollupCache::default())
+        .manage(DbState::default())
+        .manage(LiveStream::default())
+        .invoke_handler(tauri::generate_handler![
+            get_live_metrics,
+            get_rollups,
+            get_session_activity_grid,
+            get_model_switch_count,
+            get_throughput_comparison,
+            annotate_sample,
+            get_annotations,
+            get_tokens_per_second_series,
+            get_context_pressure_index,
+            get_collector_health,
+            get_model_performance_profile,
+            get_concurrent_sessions,
+            estimate_remaining_budget,
+            get_average_request_size,
+            get_session_replay,
+            set_collection_paused,
+            get_collection_paused,
+            health_check,
+            get_session_key_prefix_groups,
+            get_tokens_per_usd,
+            get_trend_slope,
+            get_cost_by_project,
+            get_recent_activity_pulse,
+            get_token_velocity_change,
+            find_similar_sessions,
+            compact_session_samples,
+            get_cost_forecast_series,
+            get_sessions_approaching_context_limit,
+            delete_samples_by_model,
+            delete_samples_before,
+            get_input_output_ratio_series,
+            get_token_economy_report,
+            get_window_delta_cached,
+            get_samples_schema_columns,
+            get_session_cost_breakdown,
+            get_longest_idle_session,
+            start_live_metrics_stream,
+            stop_live_metrics_stream,
+            get_session_overlap_stats,
+            get_net_rx_anomalies,
+            get_context_efficiency_score,
+            get_token_delta_distribution,
+            get_db_path_resolved,
+            get_input_token_trend,
+            get_output_token_trend,
+            get_samples_between_annotations,
+            get_unique_session_count_by_day,
+            get_network_tx_anomalies,
+            get_combined_usage_summary,
+            check_alerts,
+            get_alert_history,
+            clear_alert_history,
+            get_sample_collection_gaps,
+            get_peak_session,
+            get_context_saturation_events,
+            get_token_rate_autocorrelation,
+            get_context_window_sizes,
+            get_model_first_last_seen,
+            get_samples_with_high_percent_used,
+            get_export_manifest,
+            get_token_count_at_time,
+            get_daily_active_hours,
+            prune_orphaned_tags,
+            get_session_stability_score,
+            get_max_observed_context_window,
+            get_time_to_context_saturation,
+            get_samples_with_zero_remaining_tokens,
+            get_high_input_output_ratio_sessions,
+            get_network_to_token_ratio_anomalies,
+            set_db_path_persistent,
+            get_session_cost_over_time,
+            get_samples_by_percent_used_range,
+            get_throughput_by_context_utilization,
+            get_session_summary_stats,
+            get_average_context_tokens_per_model,
+            get_sessions_without_samples_in_range,
+            get_database_growth_rate,
+            get_model_availability_windows,
+            get_realtime_cost_rate,
+            get_percentile_cost_sessions,
+            get_samples_with_unexpected_model_null,
+            get_complete_session_profile,
+            get_token_debt,
+            record_collector_event,
+            get_collector_events,
+            get_model_token_cost_comparison,
+            get_context_window_headroom,
+            get_efficiency_trend,
+            get_token_budget_forecast_by_model,
+            get_cost_sensitivity_analysis,
+            get_token_rate_percentile_by_hour,
+            get_session_reactivation_count,
+            get_realtime_dashboard_pack,
+            get_tokens_in_flight,
+            get_model_latency_profile,
+            get_session_token_efficiency_over_time,
+            get_network_bytes_by_hour,
+            get_model_context_saturation_rates,
+            get_cost_attribution_by_time_of_day,
+            get_session_first_response_latency,
+            get_top_cost_hours,
+            get_session_percentile_rank,
+            get_token_accumulation_curve,
+            get_multi_session_rollup,
+            get_cost_per_context_window_fill,
+            get_rolling_total_tokens,
+            get_sample_write_latency_stats,
+            get_daily_peak_tokens_per_s,
+            get_session_cost_variance,
+            get_tokens_vs_cost_scatter_data,
+            get_session_input_token_fraction,
+            get_model_usage_share_over_time,
+            get_average_tokens_per_active_hour,
+            get_context_fill_rate_by_model,
+            get_cost_by_day_of_week,
+            get_token_burst_frequency,
+            get_sample_deduplication_report,
+            get_multiday_heatmap,
+            get_token_consumption_by_session_age,
+            get_session_end_reason,
+            get_tokens_saved_by_caching,
+            get_model_input_output_profile,
+            get_session_interruption_index,
+            get_context_growth_profile,
+            get_global_token_velocity,
+            get_cost_per_session_minute,
+            record_budget_adjustment,
+            get_session_budget_history,
+            get_cost_breakdown_by_tag,
+            get_percentile_session_cost,
+            get_live_metrics_with_session_delta,
+            get_tokens_per_s_p50_by_model,
+            get_session_cost_at_time,
+            get_net_bytes_at_saturation,
+            get_session_list_with_stats,
+            get_cost_moving_average,
+            get_realtime_rate_trend,
+            get_all_rollups_summary,
+            get_session_complexity_score,
+            get_session_timeline_events,
+            get_context_utilization_velocity,
+            get_session_cost_efficiency_rank,
+            get_tokens_to_context_saturation,
+            get_session_metrics_at_context_pct,
+            get_database_file_hash,
+            get_samples,
+            get_sessions,
+            get_hourly_buckets,
+            export_samples,
+            get_session_peaks
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
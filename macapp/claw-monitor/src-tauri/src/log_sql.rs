@@ -0,0 +1,56 @@
+// Opt-in SQL timing: set CLAWMONITOR_LOG_SQL=1 to log each single-row query to stderr.
+
+use rusqlite::{Connection, OptionalExtension, Params, Row};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Read the env var once at startup.
+pub fn init() {
+    let on = std::env::var("CLAWMONITOR_LOG_SQL").is_ok_and(|v| v == "1");
+    ENABLED.store(on, Ordering::Relaxed);
+}
+
+// `Connection::query_row`, plus an optional "[sql] <µs>us <sql>" line on stderr.
+pub fn timed_query<T>(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+    f: impl FnOnce(&Row) -> Result<T, rusqlite::Error>,
+) -> Result<T, String> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return conn.query_row(sql, params, f).map_err(|e| e.to_string());
+    }
+    let started = Instant::now();
+    let result = conn.query_row(sql, params, f);
+    eprintln!(
+        "[sql] {}us {}",
+        started.elapsed().as_micros(),
+        sql.split_whitespace().collect::<Vec<_>>().join(" ")
+    );
+    result.map_err(|e| e.to_string())
+}
+
+// `timed_query` where no matching row is `Ok(None)` rather than an error.
+pub fn timed_query_opt<T>(
+    conn: &Connection,
+    sql: &str,
+    params: impl Params,
+    f: impl FnOnce(&Row) -> Result<T, rusqlite::Error>,
+) -> Result<Option<T>, String> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return conn
+            .query_row(sql, params, f)
+            .optional()
+            .map_err(|e| e.to_string());
+    }
+    let started = Instant::now();
+    let result = conn.query_row(sql, params, f).optional();
+    eprintln!(
+        "[sql] {}us {}",
+        started.elapsed().as_micros(),
+        sql.split_whitespace().collect::<Vec<_>>().join(" ")
+    );
+    result.map_err(|e| e.to_string())
+}
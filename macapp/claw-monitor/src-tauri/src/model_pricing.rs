@@ -0,0 +1,60 @@
+//! Built-in USD prices for `estimated_cost_usd` on `LiveMetrics` and `Rollup`.
+//!
+//! Prices are `(input, output)` USD per million tokens. A key applies to every model string
+//! containing it, and the longest matching key wins, so "claude-3-5-sonnet" also prices
+//! "claude-3-5-sonnet-20241022" and "claude-opus-4-5" beats "claude-opus-4".
+//!
+//! `CLAWMONITOR_CUSTOM_PRICING` may hold a JSON object of extra keys, e.g.
+//! `{"claude-3-opus": [15, 75], "my-model": [1, 2]}`; its entries replace built-in ones with the
+//! same key. It is read once, on first lookup; a value that doesn't parse is ignored.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const BUILTIN_PRICES: [(&str, (f64, f64)); 10] = [
+    ("claude-3-haiku", (0.25, 1.25)),
+    ("claude-3-5-haiku", (0.8, 4.0)),
+    ("claude-haiku-4", (1.0, 5.0)),
+    ("claude-3-5-sonnet", (3.0, 15.0)),
+    ("claude-3-7-sonnet", (3.0, 15.0)),
+    ("claude-sonnet-4", (3.0, 15.0)),
+    ("claude-3-opus", (15.0, 75.0)),
+    ("claude-opus-4", (15.0, 75.0)),
+    ("claude-opus-4-5", (5.0, 25.0)),
+    ("claude-opus-4-6", (5.0, 25.0)),
+];
+
+fn prices() -> &'static HashMap<String, (f64, f64)> {
+    static PRICES: OnceLock<HashMap<String, (f64, f64)>> = OnceLock::new();
+    PRICES.get_or_init(|| {
+        let mut prices: HashMap<String, (f64, f64)> = BUILTIN_PRICES
+            .iter()
+            .map(|(key, price)| (key.to_string(), *price))
+            .collect();
+        if let Some(custom) = std::env::var("CLAWMONITOR_CUSTOM_PRICING")
+            .ok()
+            .and_then(|v| serde_json::from_str::<HashMap<String, (f64, f64)>>(&v).ok())
+        {
+            prices.extend(custom);
+        }
+        prices
+    })
+}
+
+// `(input_price_per_million, output_price_per_million)` for `model`, if any key matches.
+pub fn lookup_price(model: &str) -> Option<(f64, f64)> {
+    prices()
+        .iter()
+        .filter(|(key, _)| model.contains(key.as_str()))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, price)| *price)
+}
+
+pub fn estimate_cost_usd(
+    model: Option<&str>,
+    input_tokens: Option<i64>,
+    output_tokens: Option<i64>,
+) -> Option<f64> {
+    let (in_price, out_price) = lookup_price(model?)?;
+    Some((input_tokens? as f64 * in_price + output_tokens? as f64 * out_price) / 1_000_000.0)
+}
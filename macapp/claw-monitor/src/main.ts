@@ -17,6 +17,9 @@ type LiveMetrics = {
   outTokensPerS?: number | null;
   netRxBytesPerS?: number | null;
   netTxBytesPerS?: number | null;
+
+  contextSecondsRemaining?: number | null;
+  realtimeCostPerHourUsd?: number | null;
 };
 
 type Rollup = {
@@ -28,6 +31,13 @@ type Rollup = {
   totalTokens?: number | null;
   netRxBytes?: number | null;
   netTxBytes?: number | null;
+  estimatedCostUsd?: number | null;
+  sourceCount: number;
+};
+
+type RollupsResult = {
+  rollups: Rollup[];
+  warnings: string[];
 };
 
 function fmtRate(n?: number | null, unit = "t/s") {
@@ -59,7 +69,7 @@ function fmtBytes(n?: number | null) {
 
 async function tick() {
   const m = (await invoke("get_live_metrics", { dbPath: null })) as LiveMetrics;
-  const rollups = (await invoke("get_rollups", { dbPath: null })) as Rollup[];
+  const { rollups } = (await invoke("get_rollups", { dbPath: null })) as RollupsResult;
 
   (document.querySelector("#tokens-rate") as HTMLElement).textContent = fmtRate(
     m.tokensPerS,